@@ -0,0 +1,205 @@
+// '#[memoize]' puts a function behind a cache of 'myrust::catcher', recursive calls go through the cache too:
+//     #[memoize]
+//     fn fibonacci(n: u32) -> u64 { if n < 2 { n as u64 } else { fibonacci(n - 1) + fibonacci(n - 2) } }
+// All arguments together are the key, they must be Clone + Hash + Eq, the result must be Clone.
+// The cache outlives every call, so arguments are owned: '&str', 'Vec<&u8>' or 'Cow<'a, str>' can not be memoized.
+//     #[memoize(scope = global, capacity = 1000)]
+// 'scope = thread' (the default) gives every thread a 'Catcher' of its own, 'scope = global' shares
+// a 'SharedCatcher' between threads, a value is calculated once for all of them, arguments and result must be Send then.
+// 'capacity = n' keeps the n most recently used values, without it the cache grows forever.
+// A module with the name of the function clears the cache of the scope and tells its statistics:
+//     fibonacci::clear_cache();
+//     fibonacci::stats().misses
+// The cache is not locked while the function runs, so it can call itself. A call with the same arguments
+// from inside waits for itself forever, it is a recursion without end anyway.
+// Methods, generic and async functions are not supported, a static cache can not depend on them.
+// Helper functions of the expansion are named '__memoize_*', the body of the function can call its own
+// 'calculate' or 'new_cache'.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use syn::parse::Parser;
+use syn::{Error, FnArg, Ident, ItemFn, LitInt, Pat, ReturnType};
+
+enum Scope {
+    Thread,
+    Global,
+}
+
+struct Options {
+    scope: Scope,
+    capacity: Option<LitInt>,
+}
+
+#[proc_macro_attribute]
+pub fn memoize(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let function: ItemFn = match syn::parse(item) {
+        Ok(function) => function,
+        Err(error) => return compile_errors(error),
+    };
+    let mut options = Options { scope: Scope::Thread, capacity: None };
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("scope") {
+            let scope: Ident = meta.value()?.parse()?;
+            options.scope = match scope.to_string().as_str() {
+                "thread" => Scope::Thread,
+                "global" => Scope::Global,
+                _ => return Err(Error::new(scope.span(), "the scope is 'thread' or 'global'")),
+            };
+            Ok(())
+        } else if meta.path.is_ident("capacity") {
+            let capacity: LitInt = meta.value()?.parse()?;
+            if capacity.base10_parse::<usize>()? == 0 {
+                return Err(Error::new(capacity.span(), "the capacity must be at least 1"));
+            }
+            options.capacity = Some(capacity);
+            Ok(())
+        } else {
+            Err(meta.error("expected 'scope' or 'capacity'"))
+        }
+    });
+    if let Err(error) = parser.parse(attr) {
+        return compile_errors(error);
+    }
+    match expand(function, &options) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => compile_errors(error),
+    }
+}
+
+// 'Error::to_compile_error' calls '::core::compile_error!', there is no '::core' in a 2015 edition crate
+fn compile_errors(error: Error) -> TokenStream {
+    let errors = error.into_iter().map(|error| {
+        let message = error.to_string();
+        quote_spanned!(error.span()=> compile_error!(#message);)
+    });
+    quote!(#(#errors)*).into()
+}
+
+fn expand(function: ItemFn, options: &Options) -> syn::Result<TokenStream2> {
+    let ItemFn { attrs, vis, sig, block } = function;
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new_spanned(&sig.generics, "a generic function can not be memoized"));
+    }
+    if let Some(ref asyncness) = sig.asyncness {
+        return Err(Error::new_spanned(asyncness, "an async function can not be memoized"));
+    }
+    let (mut names, mut types) = (vec![], vec![]);
+    let mut outer = sig.clone();
+    for arg in &mut outer.inputs {
+        match *arg {
+            FnArg::Receiver(ref receiver) => return Err(Error::new_spanned(receiver, "a method can not be memoized")),
+            FnArg::Typed(ref mut typed) => match *typed.pat {
+                Pat::Ident(ref mut pat) if pat.by_ref.is_none() && pat.subpat.is_none() => {
+                    let ty = &typed.ty;
+                    if let Some(message) = borrowed(quote!(#ty)) {
+                        return Err(Error::new_spanned(&typed.ty, message));
+                    }
+                    pat.mutability = None; // 'mut' is for the body, which is in '__memoize_calculate'
+                    names.push(pat.ident.clone());
+                    types.push((*typed.ty).clone());
+                }
+                ref pat => return Err(Error::new_spanned(pat, "an argument of a memoized function must be a name")),
+            },
+        }
+    }
+    let inputs = &sig.inputs;
+    let output = match sig.output {
+        ReturnType::Default => quote!(()),
+        ReturnType::Type(_, ref ty) => quote!(#ty),
+    };
+    let name = &sig.ident;
+    let capacity = options.capacity.as_ref().map(|capacity| quote!(.with_capacity(#capacity)));
+    let (cache, shared, cell, value, clear, stats) = match options.scope {
+        // the cache is not borrowed while the function runs, it can call itself
+        Scope::Thread => (
+            quote!(::myrust::catcher::Catcher<Key, #output, fn(&Key) -> #output>),
+            None,
+            quote! {
+                thread_local! {
+                    pub(super) static CACHE: ::std::cell::RefCell<Option<Cache>> = const { ::std::cell::RefCell::new(None) };
+                }
+            },
+            quote! {
+                let cached = #name::CACHE.with(|cache| cache.borrow_mut().get_or_insert_with(__memoize_new_cache).cached(&key).cloned());
+                match cached {
+                    Some(value) => value,
+                    None => {
+                        let value = __memoize_calculate_key(&key);
+                        #name::CACHE.with(|cache| cache.borrow_mut().get_or_insert_with(__memoize_new_cache).insert(key, value.clone()).clone())
+                    }
+                }
+            },
+            quote!(CACHE.with(|cache| if let Some(ref mut cache) = *cache.borrow_mut() { cache.clear() })),
+            quote!(CACHE.with(|cache| cache.borrow().as_ref().map(|cache| cache.stats()).unwrap_or_default())),
+        ),
+        Scope::Global => (
+            quote!(::myrust::catcher::SharedCatcher<Key, #output, fn(&Key) -> #output>),
+            Some(quote!(.into_shared())),
+            quote!(pub(super) static CACHE: ::std::sync::OnceLock<Cache> = ::std::sync::OnceLock::new();),
+            quote!(#name::CACHE.get_or_init(__memoize_new_cache).value(&key).unwrap_or_else(|error| panic!("{}", error))),
+            quote!(if let Some(cache) = CACHE.get() { cache.clear() }),
+            quote!(CACHE.get().map(|cache| cache.stats()).unwrap_or_default()),
+        ),
+    };
+    Ok(quote! {
+        #(#attrs)*
+        #vis #outer {
+            fn __memoize_calculate(#inputs) -> #output #block
+            fn __memoize_calculate_key(key: &#name::Key) -> #output {
+                let (#(#names,)*) = key.clone();
+                __memoize_calculate(#(#names),*)
+            }
+            fn __memoize_new_cache() -> #name::Cache {
+                ::myrust::catcher::Catcher::new(__memoize_calculate_key as fn(&#name::Key) -> #output) #capacity #shared
+            }
+            let key = (#(#names,)*);
+            #value
+        }
+
+        // the cache of the function
+        #vis mod #name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            pub(super) type Key = (#(#types,)*);
+            pub(super) type Cache = #cache;
+
+            #cell
+
+            // of this thread with 'scope = thread'
+            pub fn clear_cache() {
+                #clear
+            }
+
+            pub fn stats() -> ::myrust::catcher::CacheStats {
+                #stats
+            }
+        }
+    })
+}
+
+// why the type of an argument can not be a key of a static cache, for the first '&' or lifetime in it
+fn borrowed(ty: TokenStream2) -> Option<&'static str> {
+    for token in ty {
+        match token {
+            TokenTree::Punct(ref punct) if punct.as_char() == '&' =>
+                return Some("a borrowed argument can not be memoized, the cache keeps arguments longer than the call"),
+            TokenTree::Punct(ref punct) if punct.as_char() == '\'' =>
+                return Some("an argument with a lifetime can not be memoized, the cache keeps arguments longer than the call"),
+            TokenTree::Group(ref group) => {
+                if let Some(message) = borrowed(group.stream()) {
+                    return Some(message);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
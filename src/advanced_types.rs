@@ -62,7 +62,7 @@ fn main() {
         // "dynamically sized types", referred to as DSTs or "unsized types"
         // 'str' is unsized type
         compilation_error!(
-            let s: str = "abc";
+            let s: str = "abc"; // error[E0277]: the size for values of type `str` cannot be known at compilation time
         );
         // Rust needs to know how much memory to allocate for any value of a particular type, and all values of a type must use the same amount of memory.
         // The golden rule of dynamically sized types is that we must always put values of dynamically sized types behind a pointer of some kind.
@@ -0,0 +1,359 @@
+// body of 'crate::back_of_house::kitchen', declared with 'pub mod kitchen;' in src/lib.rs
+// Cooks are threads with a channel of tickets each, they send completions to one channel:
+//
+//   Kitchen::place ──tickets──> cook 0, cook 1, ... ──completions──> Kitchen::serve_next ──> Waiter::serve_order
+//                       ^                                                  │ the inspector finds it wrong
+//                       └───────────── back_of_house::fix_incorrect_order ─┘
+//
+// A cook spends preparation time of every dish on the clock. A ticket goes to the cook who gets free first,
+// and its times are counted from the time it was placed and the time its cook got free,
+// so with 'ManualClock' the times do not depend on thread scheduling.
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use back_of_house;
+use clock::Clock;
+use front_of_house::serving::{Dish, OrderError, OrderId, Waiter};
+
+pub type CookId = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ticket {
+    pub order: OrderId,
+    pub dishes: Vec<Dish>,
+    pub placed: Duration, // the first time, the ticket time is counted from it
+    pub queued: Duration, // the last time, differs from 'placed' for remade orders
+    pub attempt: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub ticket: Ticket,
+    pub cook: CookId,
+    pub started: Duration,
+    pub done: Duration,
+}
+
+impl Completion {
+    pub fn ticket_time(&self) -> Duration {
+        self.done - self.ticket.placed
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KitchenReport {
+    pub served: usize,
+    pub remade: usize,
+    pub throughput: f64, // served orders per hour
+    pub average_ticket_time: Duration,
+    pub queue_lengths: Vec<(Duration, usize)>, // tickets waiting for a cook, after every change
+}
+
+struct Queue {
+    length: usize,
+    history: Vec<(Duration, usize)>,
+}
+
+impl Queue {
+    fn push(&mut self, at: Duration) {
+        self.length += 1;
+        self.history.push((at, self.length));
+    }
+
+    fn pop(&mut self, at: Duration) {
+        self.length -= 1;
+        self.history.push((at, self.length));
+    }
+}
+
+// running totals of served orders, the orders themselves are not kept
+#[derive(Default)]
+struct Served {
+    count: usize,
+    ticket_time: Duration,   // of all served orders together
+    first: Option<Duration>, // the earliest placed
+    last: Duration,          // the latest done
+}
+
+impl Served {
+    fn add(&mut self, completion: &Completion) {
+        self.count += 1;
+        self.ticket_time += completion.ticket_time();
+        self.first = Some(self.first.map_or(completion.ticket.placed, |f| f.min(completion.ticket.placed)));
+        self.last = self.last.max(completion.done);
+    }
+}
+
+pub struct Kitchen {
+    tickets: Vec<Sender<Ticket>>, // of every cook, empty when the kitchen is closing
+    free_at: Vec<Duration>,       // when every cook is done with the tickets sent to it
+    completions: Receiver<Completion>,
+    cooks: Vec<JoinHandle<()>>,
+    clock: Arc<dyn Clock>,
+    queue: Arc<Mutex<Queue>>,
+    in_flight: usize,
+    unserved: VecDeque<Completion>, // cooked right, but the waiter failed to serve them, they are served first
+    inspector: Box<dyn FnMut(&Completion) -> bool>, // false - the order is cooked wrong
+    served: Served,
+    remade: usize,
+}
+
+impl Kitchen {
+    pub fn new(cooks: usize, clock: Arc<dyn Clock>) -> Kitchen {
+        assert!(cooks > 0, "a kitchen needs a cook");
+        let (done, completions) = mpsc::channel();
+        let queue = Arc::new(Mutex::new(Queue { length: 0, history: vec![] }));
+        let (tickets, cooks) = (0..cooks)
+            .map(|cook| {
+                let (sender, receiver) = mpsc::channel::<Ticket>();
+                let (done, clock, queue) = (done.clone(), clock.clone(), queue.clone());
+                let thread = thread::spawn(move || {
+                    let mut free_at = Duration::ZERO;
+                    // stops when the kitchen is closed
+                    for ticket in receiver {
+                        let started = clock.now().max(ticket.queued).max(free_at);
+                        queue.lock().unwrap().pop(started);
+                        free_at = started + back_of_house::cook_order(&ticket.dishes, &*clock);
+                        if done.send(Completion { ticket, cook, started, done: free_at }).is_err() {
+                            break;
+                        }
+                    }
+                });
+                (sender, thread)
+            })
+            .unzip::<_, _, Vec<_>, Vec<_>>();
+        Kitchen {
+            free_at: vec![Duration::ZERO; tickets.len()],
+            tickets,
+            completions,
+            cooks,
+            clock,
+            queue,
+            in_flight: 0,
+            unserved: VecDeque::new(),
+            inspector: Box::new(|_| true),
+            served: Served::default(),
+            remade: 0,
+        }
+    }
+
+    // decides if a completed order is correct, wrong ones are cooked again
+    pub fn with_inspector<F: FnMut(&Completion) -> bool + 'static>(mut self, inspector: F) -> Kitchen {
+        self.inspector = Box::new(inspector);
+        self
+    }
+
+    // the order starts cooking
+    pub fn place(&mut self, waiter: &mut Waiter, order: OrderId) -> Result<(), OrderError> {
+        let dishes = waiter.order(order)?.items.iter().map(|i| i.dish.clone()).collect();
+        waiter.start_cooking(order)?;
+        let now = self.clock.now();
+        self.send(Ticket { order, dishes, placed: now, queued: now, attempt: 1 });
+        Ok(())
+    }
+
+    // the ticket goes to the end of the queue again
+    pub fn requeue(&mut self, completion: Completion) {
+        let mut ticket = completion.ticket;
+        ticket.queued = self.clock.now().max(completion.done);
+        ticket.attempt += 1;
+        self.remade += 1;
+        self.send(ticket);
+    }
+
+    // tickets placed and not served yet, the unserved ones included
+    pub fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    // waits for the next correctly cooked order and serves it, None if nothing is cooking.
+    // An order the waiter fails to serve is kept and is the next one to serve.
+    pub fn serve_next(&mut self, waiter: &mut Waiter) -> Result<Option<Completion>, OrderError> {
+        if let Some(completion) = self.unserved.pop_front() {
+            return self.serve(waiter, completion).map(Some);
+        }
+        while self.in_flight > 0 {
+            let completion = self.completions.recv().expect("cooks have left the kitchen");
+            if (self.inspector)(&completion) {
+                return self.serve(waiter, completion).map(Some);
+            }
+            self.in_flight -= 1;
+            back_of_house::fix_incorrect_order(self, completion);
+        }
+        Ok(None)
+    }
+
+    // cooked orders the waiter failed to serve
+    pub fn unserved(&self) -> impl Iterator<Item = &Completion> {
+        self.unserved.iter()
+    }
+
+    pub fn serve_all(&mut self, waiter: &mut Waiter) -> Result<Vec<Completion>, OrderError> {
+        let mut served = vec![];
+        while let Some(completion) = self.serve_next(waiter)? {
+            served.push(completion);
+        }
+        Ok(served)
+    }
+
+    pub fn report(&self) -> KitchenReport {
+        let served = self.served.count;
+        let average_ticket_time = match served {
+            0 => Duration::ZERO,
+            n => self.served.ticket_time / n as u32,
+        };
+        let first = self.served.first.unwrap_or(Duration::ZERO);
+        let hours = (self.served.last - first).as_secs_f64() / 3600.0;
+        let mut queue_lengths = self.queue.lock().unwrap().history.clone();
+        // cooks log at their own times, sort is stable, so the same time keeps the order of events
+        queue_lengths.sort_by_key(|&(at, _)| at);
+        KitchenReport {
+            served,
+            remade: self.remade,
+            throughput: if hours > 0.0 { served as f64 / hours } else { 0.0 },
+            average_ticket_time,
+            queue_lengths,
+        }
+    }
+
+    fn serve(&mut self, waiter: &mut Waiter, completion: Completion) -> Result<Completion, OrderError> {
+        if let Err(e) = waiter.serve_order(completion.ticket.order) {
+            self.unserved.push_front(completion);
+            return Err(e);
+        }
+        self.in_flight -= 1;
+        self.served.add(&completion);
+        Ok(completion)
+    }
+
+    // to the cook who gets free first, the one with the smallest number of them
+    fn send(&mut self, ticket: Ticket) {
+        let cook = (0..self.free_at.len()).min_by_key(|&c| self.free_at[c]).unwrap();
+        let cooking: Duration = ticket.dishes.iter().map(back_of_house::preparation_time).sum();
+        self.free_at[cook] = self.free_at[cook].max(ticket.queued) + cooking;
+        self.queue.lock().unwrap().push(ticket.queued);
+        self.in_flight += 1;
+        self.tickets[cook].send(ticket).expect("cooks have left the kitchen");
+    }
+}
+
+impl Drop for Kitchen {
+    fn drop(&mut self) {
+        // closing the channel stops cooks after the tickets left
+        self.tickets.clear();
+        for cook in self.cooks.drain(..) {
+            let _ = cook.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock::ManualClock;
+    use front_of_house::serving::Menu;
+
+    fn minutes(m: u64) -> Duration {
+        Duration::from_secs(m * 60)
+    }
+
+    #[test]
+    fn one_cook_cooks_tickets_in_order() {
+        let mut waiter = Waiter::new(Menu::house());
+        let mut kitchen = Kitchen::new(1, Arc::new(ManualClock::new()));
+        for items in &[&["Rye"][..], &["Soup", "Salad"], &["Wheat"]] {
+            let order = waiter.take_order(1, items).unwrap();
+            kitchen.place(&mut waiter, order).unwrap();
+        }
+        let served = kitchen.serve_all(&mut waiter).unwrap();
+        assert_eq!(served.iter().map(|c| (c.ticket.order, c.done)).collect::<Vec<_>>(),
+                   vec![(1, minutes(6)), (2, minutes(20)), (3, minutes(26))]);
+        assert!(waiter.orders().all(|o| o.state() == ::front_of_house::serving::OrderState::Served));
+
+        let report = kitchen.report();
+        assert_eq!(report.served, 3);
+        assert_eq!(report.average_ticket_time, (minutes(6) + minutes(20) + minutes(26)) / 3);
+        assert!((report.throughput - 3.0 / 26.0 * 60.0).abs() < 1e-9);
+        assert_eq!(report.queue_lengths.last(), Some(&(minutes(20), 0)));
+    }
+
+    #[test]
+    fn report_counts_every_round_of_orders() {
+        let clock = Arc::new(ManualClock::new());
+        let mut waiter = Waiter::new(Menu::house());
+        let mut kitchen = Kitchen::new(1, clock.clone());
+        clock.set(minutes(10));
+        let order = waiter.take_order(1, &["Rye"]).unwrap();
+        kitchen.place(&mut waiter, order).unwrap();
+        kitchen.serve_all(&mut waiter).unwrap();
+        clock.set(minutes(60));
+        let order = waiter.take_order(2, &["Soup"]).unwrap();
+        kitchen.place(&mut waiter, order).unwrap();
+        kitchen.serve_all(&mut waiter).unwrap();
+
+        let report = kitchen.report();
+        assert_eq!(report.served, 2);
+        assert_eq!(report.average_ticket_time, (minutes(6) + minutes(10)) / 2);
+        // from the first placed at 10 to the last done at 70 minutes
+        assert!((report.throughput - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn incorrect_order_is_cooked_again() {
+        let mut waiter = Waiter::new(Menu::house());
+        // the first attempt of every order is wrong
+        let mut kitchen = Kitchen::new(1, Arc::new(ManualClock::new())).with_inspector(|c| c.ticket.attempt > 1);
+        let order = waiter.take_order(1, &["Soup"]).unwrap();
+        kitchen.place(&mut waiter, order).unwrap();
+        let served = kitchen.serve_next(&mut waiter).unwrap().unwrap();
+        assert_eq!((served.ticket.attempt, served.started, served.done), (2, minutes(10), minutes(20)));
+        assert_eq!(kitchen.serve_next(&mut waiter), Ok(None));
+        assert_eq!(kitchen.report().remade, 1);
+        assert_eq!(kitchen.report().average_ticket_time, minutes(20));
+    }
+
+    #[test]
+    fn all_cooks_share_the_work() {
+        let mut waiter = Waiter::new(Menu::house());
+        let mut kitchen = Kitchen::new(3, Arc::new(ManualClock::new()));
+        for _ in 0..30 {
+            let order = waiter.take_order(2, &["Salad"]).unwrap();
+            kitchen.place(&mut waiter, order).unwrap();
+        }
+        assert_eq!(kitchen.in_flight(), 30);
+        let served = kitchen.serve_all(&mut waiter).unwrap();
+        let mut orders: Vec<OrderId> = served.iter().map(|c| c.ticket.order).collect();
+        orders.sort();
+        assert_eq!(orders, (1..=30).collect::<Vec<_>>());
+        // the same dishes, every cook gets free in turn
+        let mut per_cook = vec![0; 3];
+        for c in &served {
+            per_cook[c.cook] += 1;
+        }
+        assert_eq!(per_cook, vec![10, 10, 10]);
+        assert_eq!(served.iter().map(|c| c.done).max(), Some(minutes(40))); // 10 salads of 4 minutes each
+        assert_eq!(kitchen.report().queue_lengths.len(), 60);
+        // a placed order can't be placed again
+        assert!(kitchen.place(&mut waiter, 1).is_err());
+    }
+
+    #[test]
+    fn order_the_waiter_fails_to_serve_is_kept() {
+        let mut waiter = Waiter::new(Menu::house());
+        let mut kitchen = Kitchen::new(1, Arc::new(ManualClock::new()));
+        let order = waiter.take_order(1, &["Rye"]).unwrap();
+        kitchen.place(&mut waiter, order).unwrap();
+        let mut stranger = Waiter::new(Menu::house()); // does not know the order
+        assert!(kitchen.serve_next(&mut stranger).is_err());
+        assert_eq!(kitchen.in_flight(), 1);
+        assert_eq!(kitchen.unserved().map(|c| c.ticket.order).collect::<Vec<_>>(), vec![order]);
+        let served = kitchen.serve_next(&mut waiter).unwrap().unwrap();
+        assert_eq!((served.ticket.order, served.done), (order, minutes(6)));
+        assert_eq!(kitchen.in_flight(), 0);
+        assert_eq!(kitchen.serve_next(&mut waiter), Ok(None));
+        assert_eq!(kitchen.report().served, 1);
+    }
+}
@@ -0,0 +1,441 @@
+// the 'Catcher' of src/closures.rs for any key and value, a calculation runs once for every key:
+//     let mut length = Catcher::new(|s: &String| s.chars().count());
+//     let cat = "кот".to_string();
+//     length.value(&cat) == &3
+//     length.value(cat)  == &3, from the cache
+// 'FallibleCatcher' keeps only values, an error is returned and the next call calculates again.
+// Both grow forever, or keep the most recently used entries when they are bounded:
+//     let mut slow = Catcher::new(slow_calculation).with_capacity(100);
+//     let mut pages = Catcher::new(load).with_max_weight(1 << 20, |_, page: &Vec<u8>| page.len());
+//     slow.stats() == CacheStats { hits, misses, evictions, .. }
+// Values can expire, then they are calculated again on the next call:
+//     let mut rates = Catcher::new(fetch_rate).with_ttl(Duration::from_secs(60)).with_clock(clock);
+// With 'with_stale_while_revalidate(window)' an expired value is still given for 'window', and 'revalidate'
+// calculates all expired values again, a background loop or an idle moment can call it.
+// A 'Catcher' refreshes nothing by itself, a stale value is given until 'revalidate' is called, and it can not be
+// called from another thread while the catcher is borrowed. 'SharedCatcher::spawn_revalidator' is a loop in
+// another thread, a stale hit wakes it up.
+// 'into_shared' makes a 'SharedCatcher' for many threads, see src/catcher/shared.rs.
+
+use std::borrow::Borrow;
+use std::convert::Infallible;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+use clock::{Clock, SystemClock};
+
+mod lru; // src/catcher/lru.rs
+pub use self::lru::CacheStats;
+use self::lru::{Lookup, Lru};
+mod shared; // src/catcher/shared.rs
+pub use self::shared::{Revalidator, SharedCatcher, SharedError};
+
+pub type EntryTtl<K, V> = Box<dyn Fn(&K, &V) -> Duration + Send>;
+
+// the cache of all catchers
+struct Core<K, V> {
+    cache: Lru<K, V>,
+    clock: Arc<dyn Clock>,
+    ttl: Option<Duration>,
+    entry_ttl: Option<EntryTtl<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> Core<K, V> {
+    fn new() -> Core<K, V> {
+        Core { cache: Lru::new(), clock: Arc::new(SystemClock::new()), ttl: None, entry_ttl: None }
+    }
+
+    fn expires(&self, key: &K, value: &V, now: Duration) -> Duration {
+        let ttl = match self.entry_ttl {
+            Some(ref entry_ttl) => Some(entry_ttl(key, value)),
+            None => self.ttl,
+        };
+        ttl.map_or(Duration::MAX, |ttl| now.saturating_add(ttl))
+    }
+
+    fn value<E, C: FnOnce(&K) -> Result<V, E>>(&mut self, key: &K, calculate: C) -> Result<&V, E> {
+        let now = self.clock.now();
+        let place = match self.cache.find(key, now) {
+            Lookup::Fresh(place) | Lookup::Stale(place) => place,
+            Lookup::Expired(place) => {
+                let value = calculate(key)?;
+                let expires = self.expires(key, &value, now);
+                self.cache.replace(place, value, expires)
+            }
+            Lookup::Missing => {
+                let value = calculate(key)?;
+                let expires = self.expires(key, &value, now);
+                self.cache.insert(key.clone(), value, expires)
+            }
+        };
+        Ok(self.cache.value(place))
+    }
+
+    // a fresh or a stale value, counts a hit or a miss
+    fn cached(&mut self, key: &K) -> Option<&V> {
+        match self.cache.find(key, self.clock.now()) {
+            Lookup::Fresh(place) | Lookup::Stale(place) => Some(self.cache.value(place)),
+            Lookup::Expired(_) | Lookup::Missing => None,
+        }
+    }
+
+    // instead of the value the key has, if it has one
+    fn insert(&mut self, key: K, value: V) -> &V {
+        let expires = self.expires(&key, &value, self.clock.now());
+        let place = match self.cache.place(&key) {
+            Some(place) => self.cache.replace(place, value, expires),
+            None => self.cache.insert(key, value, expires),
+        };
+        self.cache.value(place)
+    }
+
+    // an error keeps the old value
+    fn revalidate<E, C: FnMut(&K) -> Result<V, E>>(&mut self, mut calculate: C) -> usize {
+        let now = self.clock.now();
+        let mut refreshed = 0;
+        for key in self.cache.expired_keys(now) {
+            if let Ok(value) = calculate(&key) {
+                // the place is looked up again, an earlier replace could evict or move the entry
+                if let Some(place) = self.cache.place(&key) {
+                    let expires = self.expires(&key, &value, now);
+                    self.cache.replace(place, value, expires);
+                    refreshed += 1;
+                }
+            }
+        }
+        refreshed
+    }
+}
+
+// builders and statistics of both catchers
+macro_rules! cache_methods {
+    () => {
+        // keeps at most 'max' values, the least recently used one is evicted.
+        // A value is given by reference from the cache, so there is room for one at least.
+        pub fn with_capacity(mut self, max: usize) -> Self {
+            assert!(max > 0, "the capacity of a catcher must be at least 1");
+            self.core.cache.set_max_entries(max);
+            self
+        }
+
+        // keeps values while their total weight is at most 'max', a value heavier than 'max' is kept alone
+        pub fn with_max_weight<W>(mut self, max: usize, weigher: W) -> Self
+            where W: Fn(&K, &V) -> usize + Send + 'static
+        {
+            self.core.cache.set_weigher(max, Box::new(weigher));
+            self
+        }
+
+        // time to live of every value
+        pub fn with_ttl(mut self, ttl: Duration) -> Self {
+            self.core.ttl = Some(ttl);
+            self
+        }
+
+        // time to live of a value by the value, instead of 'with_ttl'
+        pub fn with_entry_ttl<T>(mut self, ttl: T) -> Self
+            where T: Fn(&K, &V) -> Duration + Send + 'static
+        {
+            self.core.entry_ttl = Some(Box::new(ttl));
+            self
+        }
+
+        // an expired value is given for 'window' more, until it is revalidated
+        pub fn with_stale_while_revalidate(mut self, window: Duration) -> Self {
+            self.core.cache.set_stale_for(window);
+            self
+        }
+
+        // SystemClock by default
+        pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+            self.core.clock = clock;
+            self
+        }
+
+        pub fn stats(&self) -> CacheStats {
+            self.core.cache.stats()
+        }
+
+        // cached values, expired ones too
+        pub fn len(&self) -> usize {
+            self.core.cache.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.core.cache.len() == 0
+        }
+
+        // forgets the values, the statistics stay
+        pub fn clear(&mut self) {
+            self.core.cache.clear();
+        }
+    };
+}
+
+pub struct Catcher<K, V, F>
+    where K: Hash + Eq + Clone,
+          F: Fn(&K) -> V
+{
+    core: Core<K, V>,
+    calculation: F,
+}
+
+impl<K, V, F> Catcher<K, V, F>
+    where K: Hash + Eq + Clone,
+          F: Fn(&K) -> V
+{
+    pub fn new(calculation: F) -> Catcher<K, V, F> {
+        Catcher {
+            core: Core::new(),
+            calculation,
+        }
+    }
+
+    cache_methods!();
+
+    // the key is K or &K
+    pub fn value<Q: Borrow<K>>(&mut self, key: Q) -> &V {
+        let calculation = &self.calculation;
+        match self.core.value(key.borrow(), |k| Ok::<V, Infallible>(calculation(k))) {
+            Ok(value) => value,
+            Err(never) => match never {},
+        }
+    }
+
+    pub fn value_cloned<Q: Borrow<K>>(&mut self, key: Q) -> V
+        where V: Clone
+    {
+        self.value(key).clone()
+    }
+
+    // None when the value has to be calculated, with 'insert' a calculation can run while the catcher
+    // is not borrowed, a recursive one for example
+    pub fn cached<Q: Borrow<K>>(&mut self, key: Q) -> Option<&V> {
+        self.core.cached(key.borrow())
+    }
+
+    // a value calculated outside of the catcher
+    pub fn insert(&mut self, key: K, value: V) -> &V {
+        self.core.insert(key, value)
+    }
+
+    // calculates expired values again, returns how many
+    pub fn revalidate(&mut self) -> usize {
+        let calculation = &self.calculation;
+        self.core.revalidate(|k| Ok::<V, Infallible>(calculation(k)))
+    }
+
+    // the same cache for many threads, behind an Arc
+    pub fn into_shared(self) -> SharedCatcher<K, V, F>
+        where V: Clone
+    {
+        SharedCatcher::from_core(self.core, self.calculation)
+    }
+}
+
+pub struct FallibleCatcher<K, V, E, F>
+    where K: Hash + Eq + Clone,
+          F: Fn(&K) -> Result<V, E>
+{
+    core: Core<K, V>,
+    calculation: F,
+}
+
+impl<K, V, E, F> FallibleCatcher<K, V, E, F>
+    where K: Hash + Eq + Clone,
+          F: Fn(&K) -> Result<V, E>
+{
+    pub fn new(calculation: F) -> FallibleCatcher<K, V, E, F> {
+        FallibleCatcher {
+            core: Core::new(),
+            calculation,
+        }
+    }
+
+    cache_methods!();
+
+    pub fn try_value<Q: Borrow<K>>(&mut self, key: Q) -> Result<&V, E> {
+        self.core.value(key.borrow(), &self.calculation)
+    }
+
+    pub fn try_value_cloned<Q: Borrow<K>>(&mut self, key: Q) -> Result<V, E>
+        where V: Clone
+    {
+        self.try_value(key).cloned()
+    }
+
+    // calculates expired values again, returns how many, a value which fails stays as it is
+    pub fn revalidate(&mut self) -> usize {
+        self.core.revalidate(&self.calculation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock::ManualClock;
+    use std::cell::Cell;
+
+    #[test]
+    fn calculates_once_for_every_key() {
+        let calls = Cell::new(0);
+        let mut length = Catcher::new(|s: &String| {
+            calls.set(calls.get() + 1);
+            s.chars().count()
+        });
+        let cat = "кот".to_string();
+        assert_eq!(length.value(&cat), &3);
+        assert_eq!(length.value(cat), &3);
+        assert_eq!(length.value_cloned("a".to_string()), 1);
+        assert_eq!((calls.get(), length.len()), (2, 2));
+        length.clear();
+        assert!(length.is_empty());
+
+        // values do not have to be Copy or Clone
+        let mut words = Catcher::new(|n: &usize| vec![String::from("word"); *n]);
+        assert_eq!(words.value(2).len(), 2);
+    }
+
+    #[test]
+    fn values_calculated_outside_are_kept() {
+        let mut square = Catcher::new(|n: &u32| n * n);
+        assert_eq!(square.cached(3), None);
+        assert_eq!(square.insert(3, 10), &10);
+        assert_eq!(square.cached(3), Some(&10));
+        assert_eq!(square.value(3), &10);
+        assert_eq!((square.stats().hits, square.stats().misses), (2, 1));
+    }
+
+    #[test]
+    fn errors_are_not_cached() {
+        let calls = Cell::new(0);
+        let mut parse = FallibleCatcher::new(|s: &&str| {
+            calls.set(calls.get() + 1);
+            s.parse::<u8>()
+        });
+        assert_eq!(parse.try_value("7"), Ok(&7));
+        assert_eq!(parse.try_value_cloned("7"), Ok(7));
+        assert!(parse.try_value("300").is_err());
+        assert!(parse.try_value("300").is_err());
+        assert_eq!((calls.get(), parse.len()), (3, 1));
+        assert_eq!(parse.stats(), CacheStats { hits: 1, misses: 3, evictions: 0, entries: 1, weight: 1, ..CacheStats::default() });
+    }
+
+    #[test]
+    fn least_recently_used_values_are_evicted() {
+        let calls = Cell::new(0);
+        let mut square = Catcher::new(|x: &u64| {
+            calls.set(calls.get() + 1);
+            x * x
+        }).with_capacity(2);
+        square.value(1);
+        square.value(2);
+        square.value(1); // 2 is the least recently used one now
+        square.value(3);
+        assert_eq!(calls.get(), 3);
+        square.value(1);
+        assert_eq!(calls.get(), 3);
+        square.value(2);
+        assert_eq!(calls.get(), 4);
+        let stats = square.stats();
+        assert_eq!(stats, CacheStats { hits: 2, misses: 4, evictions: 2, entries: 2, weight: 2, ..CacheStats::default() });
+        assert!((stats.hit_rate() - 2.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "the capacity of a catcher must be at least 1")]
+    fn capacity_of_nothing_is_refused() {
+        let _ = Catcher::new(|x: &u64| x * x).with_capacity(0);
+    }
+
+    #[test]
+    fn weights_limit_the_total() {
+        let mut words = Catcher::new(|n: &usize| "w".repeat(*n)).with_max_weight(10, |_, w: &String| w.len());
+        words.value(4);
+        words.value(5);
+        assert_eq!((words.len(), words.stats().weight), (2, 9));
+        words.value(3); // 4 is evicted
+        assert_eq!((words.len(), words.stats().weight, words.stats().evictions), (2, 8, 1));
+        words.value(20); // alone over the limit, it is kept alone
+        assert_eq!((words.len(), words.stats().weight, words.stats().evictions), (1, 20, 3));
+        words.value(1);
+        assert_eq!((words.len(), words.stats().weight), (1, 1));
+    }
+
+    #[test]
+    fn many_keys_keep_the_order_of_use() {
+        let mut catcher = Catcher::new(|x: &u32| x + 1).with_capacity(50);
+        for round in 0..3 {
+            for x in 0..200 {
+                assert_eq!(catcher.value(x % (60 + round * 20)), &(x % (60 + round * 20) + 1));
+            }
+        }
+        assert_eq!(catcher.len(), 50);
+        // the last 50 keys of the last round are cached, 100..200 % 100
+        let misses = catcher.stats().misses;
+        for x in 50..100 {
+            catcher.value(x);
+        }
+        assert_eq!(catcher.stats().misses, misses);
+    }
+
+    #[test]
+    fn values_expire() {
+        let clock = Arc::new(ManualClock::new());
+        let calls = Cell::new(0);
+        let mut double = Catcher::new(|x: &u32| {
+            calls.set(calls.get() + 1);
+            x * 2
+        }).with_ttl(Duration::from_secs(10)).with_clock(clock.clone());
+        double.value(1);
+        clock.advance(Duration::from_secs(9));
+        double.value(1);
+        assert_eq!(calls.get(), 1);
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(double.value(1), &2);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(double.stats().expirations, 1);
+        // the new value lives 10 seconds from now
+        clock.advance(Duration::from_secs(9));
+        double.value(1);
+        assert_eq!((calls.get(), double.len()), (2, 1));
+    }
+
+    #[test]
+    fn every_value_can_have_its_own_ttl() {
+        let clock = Arc::new(ManualClock::new());
+        let mut parse = FallibleCatcher::new(|s: &&str| s.parse::<u64>())
+            .with_entry_ttl(|_, seconds: &u64| Duration::from_secs(*seconds))
+            .with_clock(clock.clone());
+        parse.try_value("5").unwrap();
+        parse.try_value("50").unwrap();
+        clock.advance(Duration::from_secs(20));
+        parse.try_value("5").unwrap();
+        parse.try_value("50").unwrap();
+        assert_eq!((parse.stats().hits, parse.stats().expirations), (1, 1));
+    }
+
+    #[test]
+    fn stale_values_are_given_until_revalidated() {
+        let clock = Arc::new(ManualClock::new());
+        let version = Cell::new(1);
+        let mut config = Catcher::new(|name: &&str| format!("{} v{}", name, version.get()))
+            .with_ttl(Duration::from_secs(60))
+            .with_stale_while_revalidate(Duration::from_secs(30))
+            .with_clock(clock.clone());
+        assert_eq!(config.value("db"), "db v1");
+        version.set(2);
+        clock.advance(Duration::from_secs(70));
+        assert_eq!(config.value("db"), "db v1"); // stale, but at once
+        assert_eq!(config.revalidate(), 1);
+        assert_eq!(config.value("db"), "db v2");
+        assert_eq!(config.revalidate(), 0);
+
+        // too late for the stale one, it is calculated on the call
+        version.set(3);
+        clock.advance(Duration::from_secs(100));
+        assert_eq!(config.value("db"), "db v3");
+        let stats = config.stats();
+        assert_eq!((stats.hits, stats.stale_hits, stats.misses, stats.expirations), (2, 1, 2, 1));
+    }
+}
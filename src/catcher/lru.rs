@@ -0,0 +1,240 @@
+// body of 'catcher::lru', entries of a cache in the order of use.
+// Entries are in a vector and are linked in a list from the most recently used to the least recently used one,
+// the map gives the place of a key in the vector, so finding, moving to the front and evicting are O(1).
+// A removed entry is replaced by the last one of the vector, the vector has no holes.
+// An entry expires at its time, it is stale for 'stale_for' after that and expired then.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Duration;
+
+const NONE: usize = usize::MAX; // no entry, the end of the list
+
+pub type Weigher<K, V> = Box<dyn Fn(&K, &V) -> usize + Send>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64, // the value was calculated
+    pub stale_hits: u64, // hits which gave a stale value, they are in 'hits' too
+    pub expirations: u64, // misses of expired values, they are in 'misses' too
+    pub evictions: u64,
+    pub entries: usize,
+    pub weight: usize, // of all entries, the number of entries without a weigher
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            calls => self.hits as f64 / calls as f64,
+        }
+    }
+}
+
+pub enum Lookup {
+    Fresh(usize), // the place of the entry
+    Stale(usize),
+    Expired(usize),
+    Missing,
+}
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    weight: usize,
+    expires: Duration, // Duration::MAX never
+    newer: usize,
+    older: usize,
+}
+
+pub struct Lru<K, V> {
+    places: HashMap<K, usize>,
+    entries: Vec<Entry<K, V>>,
+    newest: usize,
+    oldest: usize,
+    max_entries: Option<usize>,
+    max_weight: Option<usize>,
+    weigher: Option<Weigher<K, V>>,
+    stale_for: Duration,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> Lru<K, V> {
+    pub fn new() -> Lru<K, V> {
+        Lru {
+            places: HashMap::new(),
+            entries: vec![],
+            newest: NONE,
+            oldest: NONE,
+            max_entries: None,
+            max_weight: None,
+            weigher: None,
+            stale_for: Duration::ZERO,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn set_max_entries(&mut self, max: usize) {
+        self.max_entries = Some(max);
+        self.evict();
+    }
+
+    pub fn set_weigher(&mut self, max_weight: usize, weigher: Weigher<K, V>) {
+        self.stats.weight = self.entries.iter().map(|e| weigher(&e.key, &e.value)).sum();
+        for e in &mut self.entries {
+            e.weight = weigher(&e.key, &e.value);
+        }
+        self.max_weight = Some(max_weight);
+        self.weigher = Some(weigher);
+        self.evict();
+    }
+
+    pub fn set_stale_for(&mut self, stale_for: Duration) {
+        self.stale_for = stale_for;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // the entry of the key becomes the most recently used one, counts a hit or a miss
+    pub fn find(&mut self, key: &K, now: Duration) -> Lookup {
+        let place = match self.places.get(key) {
+            Some(&place) => place,
+            None => {
+                self.stats.misses += 1;
+                return Lookup::Missing;
+            }
+        };
+        self.unlink(place);
+        self.push_newest(place);
+        let expires = self.entries[place].expires;
+        if now < expires {
+            self.stats.hits += 1;
+            Lookup::Fresh(place)
+        } else if now < expires.saturating_add(self.stale_for) {
+            self.stats.hits += 1;
+            self.stats.stale_hits += 1;
+            Lookup::Stale(place)
+        } else {
+            self.stats.misses += 1;
+            self.stats.expirations += 1;
+            Lookup::Expired(place)
+        }
+    }
+
+    // does not count or move anything
+    pub fn place(&self, key: &K) -> Option<usize> {
+        self.places.get(key).cloned()
+    }
+
+    // keys of entries which are not fresh at 'now'
+    pub fn expired_keys(&self, now: Duration) -> Vec<K> {
+        self.entries.iter().filter(|e| e.expires <= now).map(|e| e.key.clone()).collect()
+    }
+
+    // the key must not be in the cache, older entries are evicted to make room for the new one
+    pub fn insert(&mut self, key: K, value: V, expires: Duration) -> usize {
+        let weight = self.weigher.as_ref().map_or(1, |w| w(&key, &value));
+        let place = self.entries.len();
+        self.places.insert(key.clone(), place);
+        self.entries.push(Entry { key, value, weight, expires, newer: NONE, older: NONE });
+        self.push_newest(place);
+        self.stats.weight += weight;
+        self.stats.entries = self.entries.len();
+        self.evict();
+        // evicting moves entries, the new one is still the newest
+        self.newest
+    }
+
+    // a new value for the entry, it becomes the most recently used one
+    pub fn replace(&mut self, place: usize, value: V, expires: Duration) -> usize {
+        let weight = self.weigher.as_ref().map_or(1, |w| w(&self.entries[place].key, &value));
+        self.stats.weight = self.stats.weight - self.entries[place].weight + weight;
+        let entry = &mut self.entries[place];
+        entry.value = value;
+        entry.weight = weight;
+        entry.expires = expires;
+        self.unlink(place);
+        self.push_newest(place);
+        self.evict();
+        self.newest
+    }
+
+    pub fn value(&self, place: usize) -> &V {
+        &self.entries[place].value
+    }
+
+    pub fn clear(&mut self) {
+        self.places.clear();
+        self.entries.clear();
+        self.newest = NONE;
+        self.oldest = NONE;
+        self.stats.entries = 0;
+        self.stats.weight = 0;
+    }
+
+    // the oldest entries leave until the limits hold, the newest one stays even if it alone is over the limit
+    fn evict(&mut self) {
+        loop {
+            let over = self.max_entries.is_some_and(|max| self.entries.len() > max)
+                || self.max_weight.is_some_and(|max| self.stats.weight > max);
+            if !over || self.oldest == self.newest {
+                return;
+            }
+            let oldest = self.oldest;
+            self.remove(oldest);
+            self.stats.evictions += 1;
+        }
+    }
+
+    fn remove(&mut self, place: usize) {
+        self.unlink(place);
+        let last = self.entries.len() - 1;
+        if place != last {
+            // the last entry moves to 'place', its neighbours must point there
+            let (newer, older) = (self.entries[last].newer, self.entries[last].older);
+            self.set_older(newer, place);
+            self.set_newer(older, place);
+            *self.places.get_mut(&self.entries[last].key).unwrap() = place;
+        }
+        let entry = self.entries.swap_remove(place);
+        self.places.remove(&entry.key);
+        self.stats.weight -= entry.weight;
+        self.stats.entries = self.entries.len();
+    }
+
+    fn unlink(&mut self, place: usize) {
+        let (newer, older) = (self.entries[place].newer, self.entries[place].older);
+        self.set_older(newer, older);
+        self.set_newer(older, newer);
+    }
+
+    fn push_newest(&mut self, place: usize) {
+        let newest = self.newest;
+        self.entries[place].newer = NONE;
+        self.entries[place].older = newest;
+        self.set_newer(newest, place);
+        self.newest = place;
+    }
+
+    // 'of' is NONE past the ends of the list, the end moves then
+    fn set_older(&mut self, of: usize, older: usize) {
+        match of {
+            NONE => self.newest = older,
+            _ => self.entries[of].older = older,
+        }
+    }
+
+    fn set_newer(&mut self, of: usize, newer: usize) {
+        match of {
+            NONE => self.oldest = newer,
+            _ => self.entries[of].newer = newer,
+        }
+    }
+}
@@ -0,0 +1,382 @@
+// body of 'catcher::shared', a catcher for many threads:
+//     let slow = Arc::new(Catcher::new(slow_calculation).with_capacity(100).into_shared());
+//     let s = slow.clone();
+//     thread::spawn(move || s.value(&7));
+//     slow.value(&7) // calculated once, one of the threads waits for the other one
+// The cache is locked only to look a value up or to put it in, a calculation runs unlocked.
+// A key which is being calculated is "in flight", callers of the same key wait for that calculation,
+// forever with 'value' or not longer than a timeout with 'value_timeout', the timeout is real time.
+// A panic in a calculation is an error for the caller and for everyone waiting, nothing is cached then.
+// A revalidator thread calculates expired values again every 'interval' of real time, and at once after a stale hit:
+//     let revalidator = SharedCatcher::spawn_revalidator(&rates, Duration::from_secs(60));
+// it stops when it is dropped or when the catcher is gone.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use checks::panic_message;
+use super::lru::Lookup;
+use super::{CacheStats, Core};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SharedError {
+    Panicked(String), // the message of the panic
+    Timeout,
+}
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SharedError::Panicked(ref message) => write!(f, "calculation panicked: {}", message),
+            SharedError::Timeout => write!(f, "timed out waiting for the calculation"),
+        }
+    }
+}
+
+impl std::error::Error for SharedError {}
+
+// the result of one calculation for everyone who waits for it
+struct Flight<V> {
+    result: Mutex<Option<Result<V, SharedError>>>,
+    done: Condvar,
+}
+
+struct State<K, V> {
+    core: Core<K, V>,
+    in_flight: HashMap<K, Arc<Flight<V>>>,
+}
+
+impl<K: Hash + Eq + Clone, V> State<K, V> {
+    // the flight of another thread, None when this thread has put the key in flight and must calculate it
+    fn join_flight(&mut self, key: &K) -> Option<Arc<Flight<V>>> {
+        match self.in_flight.get(key) {
+            Some(flight) => Some(flight.clone()),
+            None => {
+                let flight = Arc::new(Flight { result: Mutex::new(None), done: Condvar::new() });
+                self.in_flight.insert(key.clone(), flight);
+                None
+            }
+        }
+    }
+}
+
+// the revalidator waits on it, a stale value was given or the revalidator is stopped
+#[derive(Default)]
+struct Wakeup {
+    woken: Mutex<bool>,
+    signal: Condvar,
+}
+
+impl Wakeup {
+    fn wake(&self) {
+        *lock(&self.woken) = true;
+        self.signal.notify_all();
+    }
+
+    fn wait(&self, timeout: Duration) {
+        let woken = lock(&self.woken);
+        let (mut woken, _) = self.signal.wait_timeout_while(woken, timeout, |woken| !*woken)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *woken = false;
+    }
+}
+
+pub struct SharedCatcher<K, V, F>
+    where K: Hash + Eq + Clone,
+          F: Fn(&K) -> V
+{
+    state: Mutex<State<K, V>>,
+    wakeup: Arc<Wakeup>,
+    calculation: F,
+}
+
+// stops the thread of 'spawn_revalidator' when dropped
+pub struct Revalidator {
+    stop: Arc<AtomicBool>,
+    wakeup: Arc<Wakeup>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Revalidator {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        self.wakeup.wake();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<K, V, F> SharedCatcher<K, V, F>
+    where K: Hash + Eq + Clone,
+          V: Clone,
+          F: Fn(&K) -> V
+{
+    pub(super) fn from_core(core: Core<K, V>, calculation: F) -> SharedCatcher<K, V, F> {
+        SharedCatcher {
+            state: Mutex::new(State { core, in_flight: HashMap::new() }),
+            wakeup: Arc::new(Wakeup::default()),
+            calculation,
+        }
+    }
+
+    // waits while another thread calculates the value
+    pub fn value(&self, key: &K) -> Result<V, SharedError> {
+        self.value_waiting(key, None)
+    }
+
+    // Err(Timeout) when another thread calculates the value longer than 'timeout'
+    pub fn value_timeout(&self, key: &K, timeout: Duration) -> Result<V, SharedError> {
+        self.value_waiting(key, Some(timeout))
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.lock().core.cache.stats()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().core.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // calculations in flight finish and put their values in
+    pub fn clear(&self) {
+        self.lock().core.cache.clear();
+    }
+
+    // calculates expired values again, returns how many, the cache is not locked while it calculates.
+    // A key in flight is skipped, the thread which calculates it puts the new value in.
+    pub fn revalidate(&self) -> usize {
+        let keys = {
+            let state = self.lock();
+            state.core.cache.expired_keys(state.core.clock.now())
+        };
+        let mut refreshed = 0;
+        for key in keys {
+            let in_flight = self.lock().join_flight(&key).is_some();
+            // a panic keeps the old value
+            if !in_flight && self.calculate(&key).is_ok() {
+                refreshed += 1;
+            }
+        }
+        refreshed
+    }
+
+    // a thread which revalidates the catcher, it holds only a weak reference to it
+    pub fn spawn_revalidator(catcher: &Arc<Self>, interval: Duration) -> Revalidator
+        where K: Send + 'static,
+              V: Send + 'static,
+              F: Send + Sync + 'static
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let (weak, stopped, wakeup) = (Arc::downgrade(catcher), stop.clone(), catcher.wakeup.clone());
+        let thread = thread::spawn(move || loop {
+            wakeup.wait(interval);
+            if stopped.load(Ordering::SeqCst) {
+                return;
+            }
+            match weak.upgrade() {
+                Some(catcher) => catcher.revalidate(),
+                None => return,
+            };
+        });
+        Revalidator { stop, wakeup: catcher.wakeup.clone(), thread: Some(thread) }
+    }
+
+    fn value_waiting(&self, key: &K, timeout: Option<Duration>) -> Result<V, SharedError> {
+        let flight = {
+            let mut state = self.lock();
+            let now = state.core.clock.now();
+            match state.core.cache.find(key, now) {
+                Lookup::Fresh(place) => return Ok(state.core.cache.value(place).clone()),
+                Lookup::Stale(place) => {
+                    self.wakeup.wake();
+                    return Ok(state.core.cache.value(place).clone());
+                }
+                Lookup::Expired(_) | Lookup::Missing => {}
+            }
+            state.join_flight(key)
+        };
+        match flight {
+            Some(flight) => wait(&flight, timeout),
+            None => self.calculate(key),
+        }
+    }
+
+    // this thread has put the key in flight
+    fn calculate(&self, key: &K) -> Result<V, SharedError> {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| (self.calculation)(key)))
+            .map_err(|payload| SharedError::Panicked(panic_message(&*payload)));
+        let flight = {
+            let mut state = self.lock();
+            if let Ok(ref value) = result {
+                state.core.insert(key.clone(), value.clone());
+            }
+            // a caller which comes now finds the value in the cache, or calculates it again after a panic
+            state.in_flight.remove(key).expect("the key is in flight")
+        };
+        *lock(&flight.result) = Some(result.clone());
+        flight.done.notify_all();
+        result
+    }
+
+    // a panic while the cache is locked (in a weigher) does not make it unusable
+    fn lock(&self) -> MutexGuard<'_, State<K, V>> {
+        lock(&self.state)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn wait<V: Clone>(flight: &Flight<V>, timeout: Option<Duration>) -> Result<V, SharedError> {
+    let result = lock(&flight.result);
+    let result = match timeout {
+        None => flight.done.wait_while(result, |r| r.is_none()).unwrap_or_else(|p| p.into_inner()),
+        Some(timeout) => flight.done.wait_timeout_while(result, timeout, |r| r.is_none()).unwrap_or_else(|p| p.into_inner()).0,
+    };
+    result.clone().unwrap_or(Err(SharedError::Timeout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Catcher;
+    use super::*;
+    use checks::quietly;
+    use clock::ManualClock;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::{mpsc, Barrier};
+    use std::time::Instant;
+
+    #[test]
+    fn one_calculation_for_many_threads() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let square = Arc::new(Catcher::new(move |x: &u64| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(50));
+            x * x
+        }).into_shared());
+        let barrier = Arc::new(Barrier::new(8));
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let (square, barrier) = (square.clone(), barrier.clone());
+                thread::spawn(move || {
+                    barrier.wait();
+                    square.value(&12)
+                })
+            })
+            .collect();
+        for t in threads {
+            assert_eq!(t.join().unwrap(), Ok(144));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(square.value(&12), Ok(144));
+        let stats = square.stats();
+        assert_eq!((stats.hits + stats.misses, square.len()), (9, 1));
+    }
+
+    #[test]
+    fn waiting_can_time_out() {
+        let (started, start) = (Arc::new(Barrier::new(2)), Arc::new(Barrier::new(2)));
+        let (s, go) = (started.clone(), start.clone());
+        let slow = Arc::new(Catcher::new(move |x: &u32| {
+            s.wait();
+            go.wait();
+            *x
+        }).into_shared());
+        let s = slow.clone();
+        let calculating = thread::spawn(move || s.value(&1));
+        started.wait(); // the value is in flight
+        assert_eq!(slow.value_timeout(&1, Duration::from_millis(10)), Err(SharedError::Timeout));
+        start.wait();
+        assert_eq!(calculating.join().unwrap(), Ok(1));
+        assert_eq!(slow.value_timeout(&1, Duration::from_millis(10)), Ok(1));
+    }
+
+    #[test]
+    fn panics_are_errors_and_are_not_cached() {
+        let clock = Arc::new(ManualClock::new());
+        let fail = Arc::new(AtomicUsize::new(1));
+        let f = fail.clone();
+        let divide = Arc::new(Catcher::new(move |x: &u32| 100 / (*x - f.load(Ordering::SeqCst) as u32))
+            .with_ttl(Duration::from_secs(1))
+            .with_clock(clock.clone())
+            .into_shared());
+        let result = quietly(|| divide.value(&1));
+        assert!(matches!(result, Err(SharedError::Panicked(ref m)) if m.contains("divide by zero")), "{:?}", result);
+        // the cache works after the panic
+        fail.store(0, Ordering::SeqCst);
+        assert_eq!(divide.value(&1), Ok(100));
+        assert_eq!(divide.value(&4), Ok(25));
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(divide.revalidate(), 2);
+        assert_eq!(divide.stats().misses, 3);
+    }
+
+    #[test]
+    fn revalidation_skips_a_key_in_flight() {
+        let clock = Arc::new(ManualClock::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (started, calculating) = mpsc::channel();
+        let (go, waiting) = mpsc::channel::<()>();
+        let (started, waiting) = (Mutex::new(started), Mutex::new(waiting));
+        let counted = calls.clone();
+        let tens = Arc::new(Catcher::new(move |n: &u32| {
+                if counted.fetch_add(1, Ordering::SeqCst) == 1 {
+                    lock(&started).send(()).unwrap();
+                    lock(&waiting).recv().unwrap();
+                }
+                n * 10
+            })
+            .with_ttl(Duration::from_secs(1))
+            .with_clock(clock.clone())
+            .into_shared());
+        assert_eq!(tens.value(&1), Ok(10));
+        clock.advance(Duration::from_secs(2));
+        let reader = {
+            let tens = tens.clone();
+            thread::spawn(move || tens.value(&1))
+        };
+        calculating.recv().unwrap(); // the reader calculates the expired value
+        assert_eq!(tens.revalidate(), 0);
+        go.send(()).unwrap();
+        assert_eq!(reader.join().unwrap(), Ok(10));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(tens.revalidate(), 0); // fresh again
+    }
+
+    #[test]
+    fn stale_hit_wakes_the_revalidator() {
+        let clock = Arc::new(ManualClock::new());
+        let rate = Arc::new(AtomicUsize::new(1));
+        let r = rate.clone();
+        let rates = Arc::new(Catcher::new(move |_: &&str| r.load(Ordering::SeqCst))
+            .with_ttl(Duration::from_secs(1))
+            .with_stale_while_revalidate(Duration::from_secs(60))
+            .with_clock(clock.clone())
+            .into_shared());
+        let revalidator = SharedCatcher::spawn_revalidator(&rates, Duration::from_secs(3600));
+        assert_eq!(rates.value(&"EUR"), Ok(1));
+        rate.store(2, Ordering::SeqCst);
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(rates.value(&"EUR"), Ok(1)); // stale, given at once
+        // nobody calls 'revalidate', the interval is an hour, the stale hit has woken the revalidator
+        let started = Instant::now();
+        while rates.value(&"EUR") != Ok(2) {
+            assert!(started.elapsed() < Duration::from_secs(10), "the stale value was not revalidated");
+            thread::sleep(Duration::from_millis(1));
+        }
+        drop(revalidator);
+        assert_eq!(Arc::strong_count(&rates), 1);
+    }
+}
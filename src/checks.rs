@@ -0,0 +1,88 @@
+// backend of 'runtime_error!' and 'debug_runtime_error!', turns runtime error examples of the lessons into checks
+
+use std::any::Any;
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+pub const CHECK_RUNTIME_ERRORS: bool = cfg!(feature = "check-runtime-errors");
+
+// 'in_test' is 'cfg!(test)' of the crate which calls the macro, the library can't see it
+pub fn runtime_checks_enabled(in_test: bool) -> bool {
+    CHECK_RUNTIME_ERRORS || in_test
+}
+
+// runs 'statement', it must panic, the panic message must contain 'expected'
+pub fn expect_panic<F: FnOnce()>(file: &str, line: u32, expected: Option<&str>, statement: F) {
+    match panic::catch_unwind(AssertUnwindSafe(statement)) {
+        Ok(()) => panic!("{}:{}: runtime_error! statement did not panic", file, line),
+        Err(payload) => {
+            let message = panic_message(&*payload);
+            match expected {
+                Some(expected) if !message.contains(expected) => panic!(
+                    "{}:{}: runtime_error! statement panicked with '{}', expected '{}'",
+                    file, line, message, expected
+                ),
+                _ => println!("{}:{}: panicked as expected with '{}'", file, line, message),
+            }
+        }
+    }
+}
+
+// release build of 'debug_runtime_error!', 'expression' must not panic and must give 'expected'
+pub fn expect_value<T, F>(file: &str, line: u32, expected: T, expression: F)
+    where T: PartialEq + Debug, F: FnOnce() -> T
+{
+    match panic::catch_unwind(AssertUnwindSafe(expression)) {
+        Ok(ref value) if *value == expected => println!("{}:{}: gives {:?} as expected", file, line, value),
+        Ok(value) => panic!("{}:{}: debug_runtime_error! gives {:?} in release build, expected {:?}", file, line, value, expected),
+        Err(payload) => panic!(
+            "{}:{}: debug_runtime_error! panicked in release build with '{}'",
+            file, line, panic_message(&*payload)
+        ),
+    }
+}
+
+// release build of 'debug_runtime_error!' without an expected value
+pub fn expect_no_panic<F: FnOnce()>(file: &str, line: u32, statement: F) {
+    expect_value(file, line, (), statement)
+}
+
+thread_local! {
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+static QUIET_HOOK: Once = Once::new();
+
+// runs 'f', panics of this thread inside it are not printed, 'f' catches them itself.
+// The hook is set once for the whole process and asks the thread, panics of other threads are printed as usual.
+pub fn quietly<R, F: FnOnce() -> R>(f: F) -> R {
+    QUIET_HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.with(|quiet| quiet.get()) {
+                hook(info)
+            }
+        }));
+    });
+    // put back by a guard, 'f' may panic too
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let _ = QUIET.try_with(|quiet| quiet.set(self.0));
+        }
+    }
+    let _restore = Restore(QUIET.with(|quiet| quiet.replace(true)));
+    f()
+}
+
+// 'panic!("literal")' gives &str payload, 'panic!("{}", x)' gives String
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        String::from("<non-string panic payload>")
+    }
+}
@@ -0,0 +1,96 @@
+// time source which can be replaced in tests and simulations
+// 'now' is the time passed since the clock was made, not a wall clock time.
+//     let clock = ManualClock::new();
+//     clock.advance(Duration::from_secs(60));
+//     assert_eq!(clock.now(), Duration::from_secs(60));
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Duration;
+    fn sleep(&self, duration: Duration);
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock { start: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration)
+    }
+}
+
+// real time going 'speedup' times faster: with 'ScaledClock::new(600)' a minute passes in 100ms
+pub struct ScaledClock {
+    start: Instant,
+    speedup: u32,
+}
+
+impl ScaledClock {
+    pub fn new(speedup: u32) -> ScaledClock {
+        assert!(speedup > 0, "speedup must be positive");
+        ScaledClock { start: Instant::now(), speedup }
+    }
+}
+
+impl Clock for ScaledClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed() * self.speedup
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration / self.speedup)
+    }
+}
+
+// time stands still until it is moved by 'advance' or 'set', 'sleep' returns at once
+pub struct ManualClock {
+    now: Mutex<Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock { now: Mutex::new(Duration::ZERO) }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+
+    pub fn set(&self, now: Duration) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> ManualClock {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep(&self, _duration: Duration) {}
+}
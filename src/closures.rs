@@ -14,6 +14,7 @@ Closures can capture values from their environment in three ways, these are enco
 3. Fn borrows values from the environment immutably.
 Compiler decides which of type a closure will be.
  */
+// the same for any key and value, bounded, expiring and shared by threads is 'myrust::catcher::Catcher'
 struct Catcher<T>
     where T: Fn(u32) -> u32
 {
@@ -129,7 +130,7 @@ fn main() {
     {
         // "returning" closures from functions
         compilation_error!(
-            fn returns_closure() -> dyn Fn(i32) -> i32 { // 'dyn Fn(i32) -> i32' doesn't have a size known at compile-time
+            fn returns_closure() -> dyn Fn(i32) -> i32 { // return type cannot be a trait object without pointer indirection, doesn't have a size known at compile-time
                 |x| x + 1
             }
         );
@@ -169,7 +170,7 @@ fn calling_closure_right_where_we_define_it() {
 
 #[test]
 fn force_closure_to_take_ownership() {
-    let v = Verbose { id: 1 };
+    let v = Verbose::new(1);
     println!("Before moving verbose to closure");
     let print_verbose = move || println!("This is verbose {:?}", v);
     compilation_error!(
@@ -120,7 +120,7 @@ fn main() {
         fn foo((x, y): (i32, i32)){} // OK, x and y matches everything
 
         compilation_error!(
-            let Some(x) = Option::from(1); // refutable pattern in local binding: `None` not covered
+            let Some(x) = Option::from(1); // refutable pattern in local binding, pattern `None` not covered
         );
 
         if let Some(x) = Option::from(1) {
@@ -139,12 +139,13 @@ fn main() {
         // struct destruction
         let v = Verbose::new(3);
         match v {
-            Verbose { id} => println!("match: id of given Verbose is {}", id)
+            // '..' - the rest of fields, Verbose has a private one, which can not be named here
+            Verbose { id, .. } => println!("match: id of given Verbose is {}", id)
         }
         let id = v.id; // 'v' is valid here
         println!("after: id of given Verbose is {}", id);
 
-        let Verbose { id: the_id } = v;
+        let Verbose { id: the_id, .. } = v;
         println!("let: id of given Verbose is {}", the_id);
     }
     {
@@ -182,9 +183,9 @@ fn main() {
                 println!("Some numbers: {}, {}", first, last);
             }
         }
+        // using .. must be unambiguous
         compilation_error!(
             match numbers {
-                // using .. must be unambiguous
                 (.., second, ..) => { // `..` can only be used once per tuple pattern
                     println!("Some numbers: {}", second)
                 }
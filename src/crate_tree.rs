@@ -16,6 +16,46 @@ fn main() {
         use myrust::eat_at_restaurant as ololo_func;
         ololo_func();
     }
+    {
+        // the restaurant is a library, a binary can drive it
+        use myrust::front_of_house::hosting::{table_number, Host};
+        use std::time::Duration;
+        let minute = Duration::from_secs(60);
+        let mut host = Host::new(&[2, 4]);
+        host.add_to_waitlist(4, Duration::ZERO).unwrap();
+        host.add_to_waitlist(2, 5 * minute).unwrap();
+        let late = host.add_to_waitlist(3, 10 * minute).unwrap();
+        let seated = host.seat_all(10 * minute);
+        for seating in &seated {
+            println!("Party {} of {} is seated at table {}", seating.party, seating.size, table_number(seating.table));
+        }
+        println!("Party {} waits about {} minutes", late, host.estimated_wait(late, 10 * minute).unwrap().as_secs() / 60);
+        host.leave(seated[0].table, 50 * minute).unwrap(); // the party of 4
+        println!("Tables were occupied {:.0}% of time", host.utilization(60 * minute) * 100.0);
+
+        use myrust::front_of_house::serving::{Menu, Tip, Waiter};
+        let mut waiter = Waiter::new(Menu::house());
+        let order = waiter.take_order(seated[0].table, &["Rye", "Soup"]).unwrap();
+        waiter.start_cooking(order).unwrap();
+        waiter.serve_order(order).unwrap();
+        println!("{}", waiter.take_payment(order, Tip::Percent(15)).unwrap());
+
+        // two cooks, a minute of the kitchen passes in 10ms
+        use myrust::back_of_house::kitchen::Kitchen;
+        use myrust::clock::ScaledClock;
+        use std::sync::Arc;
+        let mut kitchen = Kitchen::new(2, Arc::new(ScaledClock::new(6000)));
+        for items in &[&["Wheat", "Salad"][..], &["Soup"], &["Rye"], &["Salad"]] {
+            let order = waiter.take_order(seated[1].table, items).unwrap();
+            kitchen.place(&mut waiter, order).unwrap();
+        }
+        for completion in kitchen.serve_all(&mut waiter).unwrap() {
+            println!("Order {} is cooked by cook {}", completion.ticket.order, completion.cook);
+        }
+        let report = kitchen.report();
+        println!("Kitchen serves {:.1} orders per hour, a ticket takes {} minutes on average",
+                 report.throughput, report.average_ticket_time.as_secs() / 60);
+    }
     {
         use std::collections::HashMap;
         let mut hm = HashMap::new();
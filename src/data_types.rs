@@ -29,14 +29,18 @@ fn main() {
     let max_8bit_value: i8 = i8::MAX;
     println!("signed 8-bit max value is {}", max_8bit_value);
 
-    // When compiling in debug mode, Rust includes checks for integer overflow that cause the program to panic at runtime
+    // When compiling in debug mode, Rust includes checks for integer overflow that cause the program to panic at runtime.
+    // In release mode there are no checks, the value wraps around (two's complement): i8::MAX + 1 == i8::MIN
+    // (if the compiler could prove the overflow, it would be an error: this arithmetic operation will overflow)
     debug_runtime_error!(
-        let var_to_overflow = max_8bit_value + 1; // error: this arithmetic operation will overflow, attempt to compute `i8::MAX + 1_i8`, which would overflow
+        "attempt to add with overflow", release == -128,
+        let var_to_overflow = max_8bit_value + 1;
     );
 
     // whatever, still operating on i8
     debug_runtime_error!(
-        let var_to_overflow: i16 = (max_8bit_value + 1) as i16; // error: attempt to add with overflow
+        "attempt to add with overflow", release == -128, // not 128, the addition wraps before the cast
+        let var_to_overflow: i16 = (max_8bit_value + 1) as i16;
     );
 
     compilation_error!(
@@ -55,11 +59,13 @@ fn main() {
     let a: i64 = 1;
     let b: i32 = 2;
     // operator + with according types is chosen by the first argument
+    // c: i64, first argument is a: i64
     compilation_error!(
-        let c = a + b; // c: i64, first argument is a: i64, error: mismatched types, expected `i64`, found `i32`
+        let c = a + b; // error: mismatched types, expected `i64`, found `i32`
     );
+    // d: i32, first argument is b: i32
     compilation_error!(
-        let d = b + a; // d: i32, first argument is b: i32, error: mismatched types, expected `i32`, found `i64`
+        let d = b + a; // error: mismatched types, expected `i32`, found `i64`
     );
 
 // floating points
@@ -112,7 +118,7 @@ fn main() {
 
     // "fixed size" means that size if a part of a type
     compilation_error!(
-        let a: [i32; 3] = [1, 2, 3, 4]; // error: mismatched types; expected an array with a fixed size of 3 elements, found one with 4 elements
+        let a: [i32; 3] = [1, 2, 3, 4]; // error: mismatched types; expected an array with a size of 3, found one with a size of 4
     );
 
     let a = [3; 5]; // array of five elements, all are 3
@@ -1,3 +1,6 @@
+extern crate myrust;
+use myrust::runtime_error;
+
 use std::io;
 use std::process::exit;
 
@@ -21,5 +24,10 @@ fn main() {
     };
 
     println!("The {}'th element is {}", index, ar[index]);
-    // When index == 12 and compiled to Debug, then ar[index] throws error: index out of bounds: the len is 5 but the index is 12
+    // When index == 12, then ar[index] throws error: index out of bounds: the len is 5 but the index is 12
+    // Unlike integer overflow, indexes are checked in release build too, there is no reading past the end of the array
+    runtime_error!(
+        "index out of bounds: the len is 5 but the index is 12",
+        let element = ar[12];
+    );
 }
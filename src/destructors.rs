@@ -20,10 +20,11 @@ fn main() {
         // 'std::mem:drop' may be useful when do RAII
 
         println!("v(2) is dropped, before it has gone out of scope");
-        v = Verbose::new(3);
+        // 'v' is invalid
         compilation_error!(
-            let vv = v; // use of moved value: `v`, 'v' is invalid
+            let vv = v; // use of moved value: `v`
         );
+        v = Verbose::new(3); // 'v' is valid again
         println!("Going out of scope");
     }
     {
@@ -38,6 +39,44 @@ fn main() {
         // no "redefining", shadowing only, even in the same scope
         let v = Verbose::new(6); // "the last" value of 'v' is not about to be dropped
         println!("About to go out of scope");
-        // first 'v' and second 'v' dropped here
+        // second 'v' and then first 'v' are dropped here, in reverse order of declaration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use myrust::Verbose;
+    use myrust::journal;
+
+    #[test]
+    fn assignment_drops_previous_value() {
+        let recording = journal::start();
+        let mut v = Verbose::new(0);
+        v = Verbose::new(1);
+        recording.assert_drops(&[0]);
+        recording.assert_not_dropped(1);
+    }
+
+    #[test]
+    fn mem_drop_drops_before_end_of_scope() {
+        let recording = journal::start();
+        {
+            let v = Verbose::new(2);
+            std::mem::drop(v);
+            recording.assert_drops(&[2]);
+            let v = Verbose::new(3);
+        }
+        recording.assert_drops(&[2, 3]);
+    }
+
+    #[test]
+    fn shadowed_values_are_dropped_in_reverse_order() {
+        let recording = journal::start();
+        {
+            let v = Verbose::new(5);
+            let v = Verbose::new(6);
+            recording.assert_not_dropped(5); // shadowing does not drop
+        }
+        recording.assert_drops(&[6, 5]);
     }
 }
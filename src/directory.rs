@@ -0,0 +1,246 @@
+// employees of a company by departments, driven by text commands:
+//     Add Sally to Engineering
+//     Remove Sally from Engineering
+//     Move Amir to Engineering
+//     List Sales
+//     List all
+// Departments are matched ignoring case and shown as they were written first, names are matched exactly.
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Add { name: String, department: String },
+    Remove { name: String, department: String },
+    Move { name: String, department: String },
+    List(String),
+    ListAll,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirectoryError {
+    UnknownCommand(String),
+    Malformed { usage: &'static str },
+    UnknownDepartment(String),
+    UnknownEmployee(String),
+    NotInDepartment { name: String, department: String },
+    AlreadyInDepartment { name: String, department: String },
+    Ambiguous { name: String, departments: Vec<String> }, // 'Move' of a name which is in several departments
+}
+
+impl fmt::Display for DirectoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DirectoryError::UnknownCommand(ref word) =>
+                write!(f, "unknown command '{}', expected Add, Remove, Move or List", word),
+            DirectoryError::Malformed { usage } => write!(f, "malformed command, usage: {}", usage),
+            DirectoryError::UnknownDepartment(ref department) => write!(f, "there is no department {}", department),
+            DirectoryError::UnknownEmployee(ref name) => write!(f, "{} does not work here", name),
+            DirectoryError::NotInDepartment { ref name, ref department } =>
+                write!(f, "{} is not in {}", name, department),
+            DirectoryError::AlreadyInDepartment { ref name, ref department } =>
+                write!(f, "{} is already in {}", name, department),
+            DirectoryError::Ambiguous { ref name, ref departments } =>
+                write!(f, "{} is in {}, remove from all but one first", name, departments.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for DirectoryError {}
+
+const ADD: &str = "Add <name> to <department>";
+const REMOVE: &str = "Remove <name> from <department>";
+const MOVE: &str = "Move <name> to <department>";
+const LIST: &str = "List <department> | List all";
+
+impl Command {
+    // names and departments can have several words: "Add Mary Ann to Human Resources"
+    pub fn parse(line: &str) -> Result<Command, DirectoryError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let verb = match words.first() {
+            Some(verb) => verb.to_lowercase(),
+            None => return Err(DirectoryError::UnknownCommand(String::new())),
+        };
+        let split = |keyword: &str, usage: &'static str| -> Result<(String, String), DirectoryError> {
+            // the name takes at least one word
+            match words.iter().skip(2).position(|w| w.eq_ignore_ascii_case(keyword)) {
+                Some(i) if i + 3 < words.len() => Ok((words[1..i + 2].join(" "), words[i + 3..].join(" "))),
+                _ => Err(DirectoryError::Malformed { usage }),
+            }
+        };
+        match verb.as_str() {
+            "add" => split("to", ADD).map(|(name, department)| Command::Add { name, department }),
+            "remove" => split("from", REMOVE).map(|(name, department)| Command::Remove { name, department }),
+            "move" => split("to", MOVE).map(|(name, department)| Command::Move { name, department }),
+            "list" => match words.len() {
+                1 => Err(DirectoryError::Malformed { usage: LIST }),
+                2 if words[1].eq_ignore_ascii_case("all") => Ok(Command::ListAll),
+                _ => Ok(Command::List(words[1..].join(" "))),
+            },
+            _ => Err(DirectoryError::UnknownCommand(words[0].to_string())),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Directory {
+    departments: HashMap<String, Vec<String>>, // by lowercase department, names are kept sorted
+    titles: HashMap<String, String>,          // lowercase department -> as it was written first
+}
+
+impl Directory {
+    pub fn new() -> Directory {
+        Directory::default()
+    }
+
+    // parses and executes a line, returns lines to show
+    pub fn execute(&mut self, line: &str) -> Result<Vec<String>, DirectoryError> {
+        match Command::parse(line)? {
+            Command::Add { name, department } => {
+                self.add(&name, &department)?;
+                Ok(vec![format!("{} is added to {}", name, self.title(&department))])
+            }
+            Command::Remove { name, department } => {
+                self.remove(&name, &department)?;
+                Ok(vec![format!("{} is removed from {}", name, department)])
+            }
+            Command::Move { name, department } => {
+                let from = self.move_to(&name, &department)?;
+                Ok(vec![format!("{} is moved from {} to {}", name, from, self.title(&department))])
+            }
+            Command::List(department) => self.list(&department).map(|names| names.to_vec()),
+            Command::ListAll => Ok(self.list_all()
+                .into_iter()
+                .map(|(department, names)| format!("{}: {}", department, names.join(", ")))
+                .collect()),
+        }
+    }
+
+    pub fn add(&mut self, name: &str, department: &str) -> Result<(), DirectoryError> {
+        let key = department.to_lowercase();
+        let names = self.departments.entry(key.clone()).or_default();
+        match names.binary_search_by(|n| n.as_str().cmp(name)) {
+            Ok(_) => Err(DirectoryError::AlreadyInDepartment { name: name.to_string(), department: department.to_string() }),
+            Err(i) => {
+                names.insert(i, name.to_string());
+                self.titles.entry(key).or_insert_with(|| department.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    // a department without employees is gone
+    pub fn remove(&mut self, name: &str, department: &str) -> Result<(), DirectoryError> {
+        let key = department.to_lowercase();
+        let names = self.departments.get_mut(&key).ok_or_else(|| DirectoryError::UnknownDepartment(department.to_string()))?;
+        let i = names.iter().position(|n| n == name)
+            .ok_or_else(|| DirectoryError::NotInDepartment { name: name.to_string(), department: department.to_string() })?;
+        names.remove(i);
+        if names.is_empty() {
+            self.departments.remove(&key);
+            self.titles.remove(&key);
+        }
+        Ok(())
+    }
+
+    // returns the department the employee has left
+    pub fn move_to(&mut self, name: &str, department: &str) -> Result<String, DirectoryError> {
+        let mut from: Vec<String> = self.departments.iter()
+            .filter(|(_, names)| names.iter().any(|n| n == name))
+            .map(|(key, _)| self.titles[key].clone())
+            .collect();
+        match from.len() {
+            0 => Err(DirectoryError::UnknownEmployee(name.to_string())),
+            1 => {
+                let from = from.pop().unwrap();
+                if from.to_lowercase() == department.to_lowercase() {
+                    return Err(DirectoryError::AlreadyInDepartment { name: name.to_string(), department: from });
+                }
+                self.remove(name, &from)?;
+                self.add(name, department)?;
+                Ok(from)
+            }
+            _ => {
+                from.sort();
+                Err(DirectoryError::Ambiguous { name: name.to_string(), departments: from })
+            }
+        }
+    }
+
+    // sorted names
+    pub fn list(&self, department: &str) -> Result<&[String], DirectoryError> {
+        self.departments.get(&department.to_lowercase())
+            .map(|names| names.as_slice())
+            .ok_or_else(|| DirectoryError::UnknownDepartment(department.to_string()))
+    }
+
+    // departments sorted alphabetically ignoring case, names sorted in every department
+    pub fn list_all(&self) -> Vec<(&str, &[String])> {
+        let mut keys: Vec<&String> = self.departments.keys().collect();
+        keys.sort();
+        keys.into_iter().map(|k| (self.titles[k].as_str(), self.departments[k].as_slice())).collect()
+    }
+
+    fn title(&self, department: &str) -> String {
+        self.titles.get(&department.to_lowercase()).cloned().unwrap_or_else(|| department.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(directory: &mut Directory, script: &str) {
+        for line in script.lines() {
+            directory.execute(line).unwrap();
+        }
+    }
+
+    #[test]
+    fn commands_are_parsed() {
+        assert_eq!(
+            Command::parse("add Mary Ann to Human Resources"),
+            Ok(Command::Add { name: "Mary Ann".to_string(), department: "Human Resources".to_string() })
+        );
+        assert_eq!(
+            Command::parse("Remove Toto from Sales"),
+            Ok(Command::Remove { name: "Toto".to_string(), department: "Sales".to_string() })
+        );
+        assert_eq!(Command::parse("LIST ALL"), Ok(Command::ListAll));
+        assert_eq!(Command::parse("List sales"), Ok(Command::List("sales".to_string())));
+        assert_eq!(Command::parse("Add Sally Engineering"), Err(DirectoryError::Malformed { usage: ADD }));
+        assert_eq!(Command::parse("Add to Sales"), Err(DirectoryError::Malformed { usage: ADD }));
+        assert_eq!(Command::parse("Move Sally to"), Err(DirectoryError::Malformed { usage: MOVE }));
+        assert_eq!(Command::parse("List"), Err(DirectoryError::Malformed { usage: LIST }));
+        assert_eq!(Command::parse("Fire Bob").unwrap_err().to_string(),
+                   "unknown command 'Fire', expected Add, Remove, Move or List");
+    }
+
+    #[test]
+    fn departments_are_case_insensitive_and_sorted() {
+        let mut directory = Directory::new();
+        run(&mut directory, "Add Sally to Engineering\nAdd Amir to sales\nAdd Bob to SALES\nAdd Zed to engineering\nAdd Ann to Accounting");
+        assert_eq!(directory.execute("List ENGINEERING"), Ok(vec!["Sally".to_string(), "Zed".to_string()]));
+        assert_eq!(directory.execute("List all").unwrap(), vec!["Accounting: Ann", "Engineering: Sally, Zed", "sales: Amir, Bob"]);
+        assert_eq!(
+            directory.execute("Add Bob to Sales"),
+            Err(DirectoryError::AlreadyInDepartment { name: "Bob".to_string(), department: "Sales".to_string() })
+        );
+    }
+
+    #[test]
+    fn remove_and_move() {
+        let mut directory = Directory::new();
+        run(&mut directory, "Add Sally to Engineering\nAdd Amir to Sales");
+        assert_eq!(directory.execute("Move Amir to engineering").unwrap(), vec!["Amir is moved from Sales to Engineering"]);
+        assert_eq!(directory.execute("List Sales"), Err(DirectoryError::UnknownDepartment("Sales".to_string())));
+        assert_eq!(directory.execute("Remove Bob from Engineering").unwrap_err().to_string(), "Bob is not in Engineering");
+        run(&mut directory, "Remove Sally from engineering\nAdd Amir to Sales");
+        assert_eq!(
+            directory.execute("Move Amir to Support"),
+            Err(DirectoryError::Ambiguous { name: "Amir".to_string(), departments: vec!["Engineering".to_string(), "Sales".to_string()] })
+        );
+        assert_eq!(directory.execute("Move Eve to Support"), Err(DirectoryError::UnknownEmployee("Eve".to_string())));
+    }
+}
@@ -0,0 +1,58 @@
+// a text interface to the employee directory, see src/directory.rs for commands
+//     cargo run --bin employees                       commands from stdin
+//     cargo run --bin employees -- company.txt       the script first, then stdin
+// Lines of a script starting with '#' are comments, an error in a script stops it.
+
+extern crate myrust;
+
+use myrust::directory::Directory;
+
+use std::env;
+use std::fs;
+use std::io::{self, BufRead};
+use std::process::exit;
+
+fn main() {
+    let mut directory = Directory::new();
+    if let Some(path) = env::args().nth(1) {
+        let script = match fs::read_to_string(&path) {
+            Ok(script) => script,
+            Err(e) => {
+                println!("Cannot read {}: {}", path, e);
+                exit(1);
+            }
+        };
+        for (i, line) in script.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match directory.execute(line) {
+                Ok(output) => {
+                    for l in output {
+                        println!("{}", l);
+                    }
+                }
+                Err(e) => {
+                    println!("{}:{}: {}", path, i + 1, e);
+                    exit(1);
+                }
+            }
+        }
+    }
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        let line = line.expect("Cannot read input");
+        if line.trim().is_empty() {
+            continue;
+        }
+        match directory.execute(&line) {
+            Ok(output) => {
+                for l in output {
+                    println!("{}", l);
+                }
+            }
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+}
@@ -19,7 +19,7 @@ fn main() {
         let six = IpVersion::V6;
 
         compilation_error!(
-            four == A::One; // mismatched types: expected enum `IpVersion`, found enum `A`
+            four == A::One; // mismatched types, expected `IpVersion`, found `A`
         );
 
         struct IpAddress {
@@ -88,15 +88,17 @@ fn main() {
         // enum Option<T> { Some(T), None } for nul-concept
         let some_number1 = Some(1);
         let some_number2 = Some(2);
+        // absent_number: Option<<unknown>>
         compilation_error!(
-            let absent_number = None; // : Option<<unknowen>>, type annotations needed for `Option<T>`
+            let absent_number = None; // type annotations needed for `Option<_>`
         );
         let x = some_number1.unwrap() + some_number2.unwrap();
         println!("some numbers sum is {}", x);
 
         let absent_number: Option<i32> = None;
         runtime_error!(
-            let y = absent_number.unwrap(); // thread 'main' panicked at 'called `Option::unwrap()` on a `None` value'
+            "called `Option::unwrap()` on a `None` value", // thread 'main' panicked at ...
+            let y = absent_number.unwrap();
         );
         println!("Does 'absent_number' exist: {}", if absent_number.is_some() { "yes" } else { "no"});
     }
@@ -0,0 +1,201 @@
+// exercises with their own tests and hints, and progress which is kept in a file between runs.
+// An exercise is a statement, hints in the order they are shown and checks which panic or return Err when
+// the solution is wrong:
+//     const SUM: Exercise = Exercise { name: "sum", statement: "...", hints: &["..."], tests: &[("two", two)], demo: None };
+//     let report = run(&SUM);
+//     progress.record(&report);
+//     if !report.passed() { println!("{}", progress.next_hint(&SUM).unwrap_or("no more hints")) }
+// The file has a line for every exercise which was run: "<name> passed|failed <hints shown>".
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use checks::{panic_message, quietly};
+
+pub type Check = fn() -> Result<(), String>;
+
+pub struct Exercise {
+    pub name: &'static str,
+    pub statement: &'static str,
+    pub hints: &'static [&'static str],
+    pub tests: &'static [(&'static str, Check)],
+    pub demo: Option<fn()>, // shows the solution at work
+}
+
+pub struct Report {
+    pub exercise: &'static str,
+    pub results: Vec<(&'static str, Result<(), String>)>, // a panic is an Err with its message
+}
+
+impl Report {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|(_, r)| r.is_ok())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (test, result) in &self.results {
+            match result {
+                Ok(()) => writeln!(f, "    ok      {}", test)?,
+                Err(e) => writeln!(f, "    FAILED  {}: {}", test, e)?,
+            }
+        }
+        let passed = self.results.iter().filter(|(_, r)| r.is_ok()).count();
+        write!(f, "{}: {} of {} tests passed", self.exercise, passed, self.results.len())
+    }
+}
+
+// runs every test, panics are caught and their messages are not printed
+pub fn run(exercise: &Exercise) -> Report {
+    let results = exercise.tests.iter()
+        .map(|&(name, test)| {
+            let result = quietly(|| panic::catch_unwind(AssertUnwindSafe(test))).unwrap_or_else(|payload| Err(panic_message(&*payload)));
+            (name, result)
+        })
+        .collect();
+    Report { exercise: exercise.name, results }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Status {
+    pub passed: bool,
+    pub hints: usize, // how many hints were shown
+}
+
+pub struct Progress {
+    path: Option<PathBuf>, // None keeps it in memory
+    exercises: BTreeMap<String, Status>,
+}
+
+impl Progress {
+    pub fn new() -> Progress {
+        Progress { path: None, exercises: BTreeMap::new() }
+    }
+
+    // an absent file is no progress yet
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Progress> {
+        let path = path.as_ref();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let mut progress = Progress::parse(&text)?;
+        progress.path = Some(path.to_path_buf());
+        Ok(progress)
+    }
+
+    pub fn parse(text: &str) -> io::Result<Progress> {
+        let mut progress = Progress::new();
+        for (i, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let status = match words.as_slice() {
+                [_, passed @ ("passed" | "failed"), hints] => hints.parse().ok().map(|hints| Status { passed: *passed == "passed", hints }),
+                _ => None,
+            };
+            match status {
+                Some(status) => progress.exercises.insert(words[0].to_string(), status),
+                None => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("line {}: '{}' is not progress", i + 1, line))),
+            };
+        }
+        Ok(progress)
+    }
+
+    // writes the file, nothing happens for progress in memory
+    pub fn save(&self) -> io::Result<()> {
+        match self.path {
+            Some(ref path) => fs::write(path, self.to_string()),
+            None => Ok(()),
+        }
+    }
+
+    pub fn status(&self, exercise: &str) -> Option<Status> {
+        self.exercises.get(exercise).cloned()
+    }
+
+    pub fn record(&mut self, report: &Report) {
+        self.exercises.entry(report.exercise.to_string()).or_default().passed = report.passed();
+    }
+
+    // the first one not shown yet
+    pub fn next_hint(&mut self, exercise: &Exercise) -> Option<&'static str> {
+        let status = self.exercises.entry(exercise.name.to_string()).or_default();
+        let hint = exercise.hints.get(status.hints)?;
+        status.hints += 1;
+        Some(hint)
+    }
+
+    // the first one which has not passed
+    pub fn next<'a>(&self, exercises: &'a [Exercise]) -> Option<&'a Exercise> {
+        exercises.iter().find(|e| !self.status(e.name).is_some_and(|s| s.passed))
+    }
+}
+
+impl Default for Progress {
+    fn default() -> Progress {
+        Progress::new()
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, status) in &self.exercises {
+            writeln!(f, "{} {} {}", name, if status.passed { "passed" } else { "failed" }, status.hints)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok() -> Result<(), String> {
+        Ok(())
+    }
+
+    fn wrong() -> Result<(), String> {
+        Err("2 + 2 is not 5".to_string())
+    }
+
+    fn panics() -> Result<(), String> {
+        assert_eq!(2 + 2, 5, "arithmetic");
+        Ok(())
+    }
+
+    const SUM: Exercise = Exercise { name: "sum", statement: "add numbers", hints: &["use +", "2 + 2 is 4"], tests: &[("ok", ok), ("wrong", wrong), ("panics", panics)], demo: None };
+    const DONE: Exercise = Exercise { name: "done", statement: "nothing", hints: &[], tests: &[("ok", ok)], demo: None };
+
+    #[test]
+    fn failures_and_panics_are_reported() {
+        let report = run(&SUM);
+        assert!(!report.passed());
+        assert_eq!(report.results[0], ("ok", Ok(())));
+        assert_eq!(report.results[1], ("wrong", Err("2 + 2 is not 5".to_string())));
+        assert!(report.results[2].1.as_ref().unwrap_err().contains("arithmetic"));
+        assert!(report.to_string().ends_with("sum: 1 of 3 tests passed"));
+        assert!(run(&DONE).passed());
+    }
+
+    #[test]
+    fn progress_keeps_status_and_hints() {
+        let mut progress = Progress::new();
+        assert_eq!(progress.next(&[SUM, DONE]).map(|e| e.name), Some("sum"));
+        progress.record(&run(&SUM));
+        progress.record(&run(&DONE));
+        assert_eq!(progress.next_hint(&SUM), Some("use +"));
+        assert_eq!(progress.next_hint(&SUM), Some("2 + 2 is 4"));
+        assert_eq!(progress.next_hint(&SUM), None);
+        assert_eq!(progress.to_string(), "done passed 0\nsum failed 2\n");
+
+        let progress = Progress::parse(&progress.to_string()).unwrap();
+        assert_eq!(progress.status("sum"), Some(Status { passed: false, hints: 2 }));
+        assert_eq!(progress.next(&[DONE, SUM]).map(|e| e.name), Some("sum"));
+        assert_eq!(progress.status("other"), None);
+        assert!(Progress::parse("sum maybe 1").is_err());
+    }
+}
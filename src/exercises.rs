@@ -1,13 +1,120 @@
-extern crate unicode_segmentation;
+// exercises of the book with their tests and hints, progress is kept in exercises_progress.txt.
+// Your solutions are the functions below, the ones which are not written yet panic with 'todo!',
+// tests compare what they give with what myrust gives:
+//     cargo run --bin exercises                  every exercise and if its tests pass
+//     cargo run --bin exercises -- show stats    the statement and myrust's solution at work
+//     cargo run --bin exercises -- run stats     tests of your solution, the next hint when they fail
+//     cargo run --bin exercises -- hint stats    the next hint
+//     cargo run --bin exercises -- next          runs the first exercise which has not passed yet
+//     cargo run --bin exercises -- reset         forgets the progress
 
-use std::collections::HashSet;
-use std::iter::FromIterator;
+extern crate myrust;
 
-// given a and b print all numbers with unique digits
+use myrust::exercise::{self, Exercise, Progress};
+
+use std::env;
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::process::exit;
+
+const PROGRESS: &str = "exercises_progress.txt";
+
+const EXERCISES: &[Exercise] = &[
+    Exercise {
+        name: "stats",
+        statement: "Given a list of integers, use a vector and return the
+  mean (the average value),
+  median (when sorted, the value in the middle position),
+  mode (the value that occurs most often; a hash map will be helpful here) of the list.",
+        hints: &[
+            "Sum as f64, integer division of 44 by 9 gives 4.",
+            "Sort a copy of the list for the median, the mean of two middle values is the median of an even list.",
+            "Count values in a HashMap<i32, usize>, several values can occur most often.",
+        ],
+        tests: &[("mean", stats_mean), ("median", stats_median), ("modes", stats_modes)],
+        demo: Some(stats_demo),
+    },
+    Exercise {
+        name: "pig_latin",
+        statement: "Convert strings to pig latin.
+The first consonant of each word is moved to the end of the word and “ay” is added, so “first” becomes “irst-fay.”
+Words that start with a vowel have “hay” added to the end instead (“apple” becomes “apple-hay”).
+Keep in mind the details about UTF-8 encoding!",
+        hints: &[
+            "A letter is not a char, 'é' can be two of them: split text with UnicodeSegmentation::graphemes.",
+            "Keep everything between words as it is, then punctuation and spaces survive.",
+            "Vowels differ by script, 'е' is a Cyrillic vowel and not the Latin 'e'.",
+        ],
+        tests: &[("first_apple", pig_latin_first_apple), ("utf8", pig_latin_utf8)],
+        demo: Some(pig_latin_demo),
+    },
+    Exercise {
+        name: "employees",
+        statement: "Using a hash map and vectors, create a text interface to allow a user to add employee names
+to a department in a company. For example, “Add Sally to Engineering” or “Add Amir to Sales.”
+Then let the user retrieve a list of all people in a department or all people in the company
+by department, sorted alphabetically.",
+        hints: &[
+            "HashMap<String, Vec<String>> from a department to its people.",
+            "Sort a copy of the names when they are listed, a BTreeMap lists departments in order.",
+        ],
+        tests: &[("add_and_list", employees_add_and_list)],
+        demo: Some(employees_demo),
+    },
+    Exercise {
+        name: "urlify",
+        statement: "Given collections of chars, with spaces, replace spaces with '%20'.
+'length' points to the last meaning char, all symbols right to it are garbage and can be used for better purpose.
+The length of garbage tail is always enough to store the collections with spaces replaced with '%20'.",
+        hints: &[
+            "Go from the end: write the result from its last char back, it never overwrites a char not read yet.",
+            "The result is longer by two for every space.",
+        ],
+        tests: &[("spaces", urlify_spaces)],
+        demo: Some(urlify_demo),
+    },
+    Exercise {
+        name: "unique_digits",
+        statement: "Given a and b return all numbers from a to b (not including b) without repeated digits.",
+        hints: &[
+            "A bool for every digit tells if it was seen.",
+            "If 44 repeats, nothing from 4400 to 4499 can be unique.",
+        ],
+        tests: &[("diapason", unique_digits_diapason)],
+        demo: Some(unique_digits_demo),
+    },
+];
+
+// the mean, None for no numbers
+fn mean(numbers: &[i32]) -> Option<f64> {
+    todo!("the mean of {:?}", numbers)
+}
+
+// the value in the middle of sorted numbers, or the mean of two middle ones, None for no numbers
+fn median(numbers: &[i32]) -> Option<f64> {
+    todo!("the median of {:?}", numbers)
+}
+
+// all values which occur most often, in ascending order
+fn modes(numbers: &[i32]) -> Vec<i32> {
+    todo!("the modes of {:?}", numbers)
+}
+
+fn pig_latin(text: &str) -> String {
+    todo!("'{}' in pig latin", text)
+}
+
+// "Add <name> to <department>" commands, then everybody by department: "Engineering: Bob, Sally"
+fn company(commands: &[&str]) -> Vec<String> {
+    todo!("the company after {:?}", commands)
+}
+
+// given a and b print all numbers with unique digits, a negative number has the digits of its magnitude
 fn generate_n_with_unq_dig(a: i32, b: i32) -> Vec<i32> {
     (a..b).into_iter().filter(|x|{
         let mut digit_is_present = [false; 10];
-        let mut x = *x;
+        let mut x = x.unsigned_abs();
         while x > 0 {
             let r = (x % 10) as usize;
             if digit_is_present[r] {
@@ -22,7 +129,6 @@ fn generate_n_with_unq_dig(a: i32, b: i32) -> Vec<i32> {
 }
 
 /*
-// TODO how to write this in rust the right way
 // Java
 // given collections of chars, with spaces, replace spaces with '%20'.
 // 'len' pints to the last meaning char, all symbols right to it are garbage and can be used for better purpose.
@@ -50,7 +156,6 @@ int urlify(char[] str, int len) {
 }
  */
 
-#[allow(dead_code)]
 fn urlify(s: &mut [char], length: usize) -> usize {
     if length == 0 || s.len() < length {
         return 0;
@@ -93,95 +198,240 @@ fn urlify(s: &mut [char], length: usize) -> usize {
     return s.len() - back_index;
 }
 
-fn main() {
-    {
-// Given a list of integers, use a vector and return the
-//   mean (the average value),
-//   median (when sorted, the value in the middle position),
-//   mode (the value that occurs most often; a hash map will be helpful here) of the list.
+// not only spaces, everything which can not be in a path of a URL, myrust::percent writes from the end of the result
+fn percent_encode(s: &mut [char], length: usize) -> usize {
+    use myrust::percent::{encode_chars_in_place, EncodeSet};
+    encode_chars_in_place(s, length, EncodeSet::PATH).unwrap_or(0)
+}
+
+// the solution gives what myrust gives for every input
+fn agree<I: Debug + ?Sized, O: PartialEq + Debug>(inputs: &[&I], solution: &dyn Fn(&I) -> O, myrust: &dyn Fn(&I) -> O) -> Result<(), String> {
+    for &input in inputs {
+        let (given, expected) = (solution(input), myrust(input));
+        if given != expected {
+            return Err(format!("{:?} gives {:?}, expected {:?}", input, given, expected));
+        }
+    }
+    Ok(())
+}
+
+const NUMBERS: &[&[i32]] = &[&[1, 6, 2, 8, 5, 2, 9, 7, 4], &[4, 1, 3, 2], &[1, 3, 2, 1, 2, 3], &[-7], &[]];
 
-        let numbers = vec![1, 6, 2, 8, 5, 2, 9, 7, 4];
-        println!("Given {:?}", numbers);
+fn stats_mean() -> Result<(), String> {
+    check_mean(mean)
+}
+
+// 44 / 9 is not the same in the last digit when it is summed in another order
+fn check_mean(mean: fn(&[i32]) -> Option<f64>) -> Result<(), String> {
+    let rounded = |m: Option<f64>| m.map(|m| (m * 1e9).round() / 1e9);
+    agree(NUMBERS, &|n| rounded(mean(n)), &|n| rounded(myrust::stats::mean(n).ok()))
+}
+
+fn stats_median() -> Result<(), String> {
+    check_median(median)
+}
+
+fn check_median(median: fn(&[i32]) -> Option<f64>) -> Result<(), String> {
+    agree(NUMBERS, &median, &|n| myrust::stats::median(n).ok())
+}
+
+fn stats_modes() -> Result<(), String> {
+    check_modes(modes)
+}
+
+fn check_modes(modes: fn(&[i32]) -> Vec<i32>) -> Result<(), String> {
+    agree(NUMBERS, &modes, &|n| myrust::stats::modes(n).unwrap_or_default())
+}
 
-        fn mean(numbers: &Vec<i32>) -> i32 {
-            numbers.iter().sum::<i32>() / numbers.len() as i32
+fn stats_demo() {
+    let numbers = vec![1, 6, 2, 8, 5, 2, 9, 7, 4];
+    println!("Given {:?}", numbers);
+
+    // functions are in src/stats.rs, they work with any integers and floats
+    use myrust::stats::{mean, median, modes, std_dev, five_number_summary};
+    println!("mean is {:.3}", mean(&numbers).unwrap()); // 4.889, not 4 as integer division gives
+    //         0  1  2  3  4  5  6  7  8
+    // sorted [1, 2, 2, 4, 5, 6, 7, 8, 9]
+    println!("median is {}", median(&numbers).unwrap()); // 5
+    println!("median of [4, 1, 3, 2] is {}", median(&[4, 1, 3, 2]).unwrap()); // 2.5, the mean of two middle values
+    println!("mode is {:?}", modes(&numbers).unwrap()); // [2]
+    println!("modes are {:?}", modes(&[1, 3, 2, 1, 2, 3]).unwrap()); // [1, 2, 3], all of them occur twice
+    println!("standard deviation is {:.3}", std_dev(&numbers).unwrap()); // 2.685
+    println!("five-number summary is {:?}", five_number_summary(&numbers).unwrap());
+    println!("mean of nothing is {:?}", mean::<i32>(&[])); // Err(Empty)
+}
+
+fn pig_latin_first_apple() -> Result<(), String> {
+    check_pig_latin(pig_latin, &["first apple", "Hello, World!"])
+}
+
+fn pig_latin_utf8() -> Result<(), String> {
+    check_pig_latin(pig_latin, &["съешь еще этих мягких французских булок, café!"])
+}
+
+fn check_pig_latin(pig_latin: fn(&str) -> String, texts: &[&str]) -> Result<(), String> {
+    agree(texts, &pig_latin, &myrust::pig_latin::to_pig_latin)
+}
+
+fn pig_latin_demo() {
+    // graphemes, punctuation and case are kept, and it can be read back, see src/pig_latin.rs
+    use myrust::pig_latin::{from_pig_latin, to_pig_latin};
+    let text = "first apple".to_string();
+    println!("Text: '{}', pigged: '{}'", text, to_pig_latin(&text));
+    let text = "съешь еще этих мягких французских булок".to_string();
+    println!("Text: '{}', pigged: '{}'", text, to_pig_latin(&text));
+    let text = "この　世界は　いいと　訝しまない".to_string(); // この　せかいは　いいと　いぶかしまない - kana are syllables, only あいうえお are vowels
+    println!("Text: '{}', pigged: '{}'", text, to_pig_latin(&text));
+    println!("Back: '{}'", from_pig_latin(&to_pig_latin(&text)));
+}
+
+fn employees_add_and_list() -> Result<(), String> {
+    check_company(company)
+}
+
+fn check_company(company: fn(&[&str]) -> Vec<String>) -> Result<(), String> {
+    use myrust::directory::Directory;
+    let commands: &[&[&str]] = &[
+        &["Add Sally to Engineering", "Add Amir to Sales", "Add Bob to Engineering"],
+        &["Add Mary Ann to Human Resources"],
+        &[],
+    ];
+    agree(commands, &company, &|commands| {
+        let mut directory = Directory::new();
+        for command in commands {
+            directory.execute(command).unwrap();
         }
-        println!("mean is {}", mean(&numbers)); // 4
+        directory.execute("List all").unwrap()
+    })
+}
 
-        fn median(number: &Vec<i32>) -> Option<i32> {
-            if number.len() > 0 {
-                let mut sorted = number.clone();
-                sorted.sort();
-                Some(sorted[sorted.len() / 2])
-            } else {
-                None
+fn employees_demo() {
+    // the interactive version is 'cargo run --bin employees'
+    use myrust::directory::Directory;
+    let mut directory = Directory::new();
+    for command in &["Add Sally to Engineering", "Add Amir to Sales", "Add Bob to sales", "Move Amir to engineering", "List all"] {
+        match directory.execute(command) {
+            Ok(output) => println!("{} => {}", command, output.join("; ")),
+            Err(e) => println!("{} => error: {}", command, e),
+        }
+    }
+}
+
+fn urlify_spaces() -> Result<(), String> {
+    check_urlify(urlify)
+}
+
+// the texts have nothing but spaces to encode
+fn check_urlify(urlify: fn(&mut [char], usize) -> usize) -> Result<(), String> {
+    let texts: &[&str] = &[" abc c-1 ", "ab c", "", "a"];
+    let encoded = |urlify: &dyn Fn(&mut [char], usize) -> usize, text: &str| {
+        let mut chars: Vec<char> = text.chars().chain(" ".repeat(2 * text.len()).chars()).collect();
+        let length = urlify(&mut chars, text.chars().count());
+        chars[..length].iter().collect::<String>()
+    };
+    agree(texts, &|text| encoded(&urlify, text), &|text| encoded(&percent_encode, text))
+}
+
+fn urlify_demo() {
+    let text = "a path/to ü?";
+    let mut chars: Vec<char> = text.chars().chain(" ".repeat(16).chars()).collect();
+    let length = urlify(&mut chars, text.chars().count());
+    println!("'{}' urlified is '{}'", text, chars[..length].iter().collect::<String>());
+    let mut chars: Vec<char> = text.chars().chain(" ".repeat(16).chars()).collect();
+    let length = percent_encode(&mut chars, text.chars().count());
+    println!("'{}' percent-encoded is '{}'", text, chars[..length].iter().collect::<String>()); // 'a%20path/to%20%C3%BC%3F'
+}
+
+fn unique_digits_diapason() -> Result<(), String> {
+    check_unique_digits(generate_n_with_unq_dig)
+}
+
+fn check_unique_digits(generate: fn(i32, i32) -> Vec<i32>) -> Result<(), String> {
+    use myrust::unique_digits::has_unique_digits;
+    let ranges: &[&(i32, i32)] = &[&(4000, 5000), &(4523, 4524), &(4553, 4554), &(-13, 3), &(7, 7)];
+    agree(ranges, &|&(a, b)| generate(a, b), &|&(a, b)| (a..b).filter(|x| has_unique_digits(x.unsigned_abs(), 10)).collect())
+}
+
+fn unique_digits_demo() {
+    println!("from -12 to 13: {:?}", generate_n_with_unq_dig(-12, 13));
+    // myrust::unique_digits jumps over blocks like 4400..4500 instead of testing every number
+    use myrust::unique_digits::{count_unique_digit_numbers, nth_unique_digit_number};
+    println!("{} of 0..10^10 have unique digits, the largest is {:?}",
+             count_unique_digit_numbers(0..10_000_000_000u64, 10), nth_unique_digit_number(8_877_690));
+}
+
+fn find(name: &str) -> &'static Exercise {
+    EXERCISES.iter().find(|e| e.name == name).unwrap_or_else(|| {
+        let names: Vec<&str> = EXERCISES.iter().map(|e| e.name).collect();
+        println!("There is no exercise '{}', there are {}", name, names.join(", "));
+        exit(2);
+    })
+}
+
+// prints the report and the next hint if it failed
+fn run(exercise: &Exercise, progress: &mut Progress) {
+    let report = exercise::run(exercise);
+    progress.record(&report);
+    println!("{}", report);
+    if !report.passed() {
+        match progress.next_hint(exercise) {
+            Some(hint) => println!("hint: {}", hint),
+            None => println!("there are no more hints"),
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if args == ["reset"] {
+        match fs::remove_file(PROGRESS) {
+            Err(ref e) if e.kind() != io::ErrorKind::NotFound => println!("Cannot remove {}: {}", PROGRESS, e),
+            _ => println!("The progress is forgotten"),
+        }
+        return;
+    }
+    let mut progress = Progress::load(PROGRESS).unwrap_or_else(|e| {
+        println!("Cannot read {}: {}", PROGRESS, e);
+        exit(1);
+    });
+    match args.as_slice() {
+        [] | ["list"] => {
+            for e in EXERCISES {
+                let report = exercise::run(e);
+                progress.record(&report);
+                let passed = report.results.iter().filter(|(_, r)| r.is_ok()).count();
+                let hints = progress.status(e.name).map_or(0, |s| s.hints);
+                println!("[{}] {:<14} {} of {} tests, {} of {} hints",
+                         if report.passed() { "x" } else { " " }, e.name, passed, report.results.len(), hints, e.hints.len());
             }
         }
-        //         0  1  2  3  4  5  6  7  8
-        // sorted [1, 2, 2, 4, 5, 6, 7, 8, 9]
-        println!("median is {}", median(&numbers).unwrap_or(-1)); // 5
-
-        fn mode(numbers: &Vec<i32>) -> Option<i32> {
-            if numbers.len() > 0 {
-                let mut counters = std::collections::HashMap::new();
-                for n in numbers {
-                    let counter = counters.entry(n).or_insert(0);
-                    *counter += 1
-                }
-                let mut found_number = numbers.first().unwrap();
-                let mut number_counter = counters[found_number];
-                for (k, v) in counters {
-                    if v > number_counter {
-                        found_number = k;
-                        number_counter = v;
-                    }
-                }
-                Some(*found_number)
-            } else {
-                None
+        ["show", name] => {
+            let exercise = find(name);
+            println!("{}\n", exercise.statement);
+            if let Some(demo) = exercise.demo {
+                demo();
             }
         }
-        println!("mode is {}", mode(&numbers).unwrap_or(-1)); // 2
-        println!("mode is {}", mode(&vec![1, 3, 2, 1, 2, 1]).unwrap_or(-1)); // 1
-    }
-    {
-// Convert strings to pig latin.
-// The first consonant of each word is moved to the end of the word and “ay” is added, so “first” becomes “irst-fay.”
-// Words that start with a vowel have “hay” added to the end instead (“apple” becomes “apple-hay”).
-// Keep in mind the details about UTF-8 encoding!
-        extern crate unicode_segmentation;
-        use unicode_segmentation::UnicodeSegmentation;
-        fn to_pig_latin(txt: &str) -> String {
-            let vowels: HashSet<char> = HashSet::from_iter(vec![
-                'e', 'u', 'i', 'o', 'a',
-                'у','е','ы', 'а', 'о', 'я', 'ё', 'и',
-                'あ','い','う','え','お'].into_iter());
-            let mut result = String::new();
-            for word in txt.split_whitespace() {
-                result.push_str(
-                    if vowels.contains(&word.chars().nth(0).unwrap_or_else(|| ' ')) {
-                        String::from(word) + "hay"
-                    } else {
-                        let w = word.graphemes(true).skip(1).collect::<String>();
-                        w + word.graphemes(true).take(1).collect::<String>().as_str() + "ay"
-                    }.as_str()
-                );
-                result.push(' ');
+        ["run", name] => run(find(name), &mut progress),
+        ["hint", name] => match progress.next_hint(find(name)) {
+            Some(hint) => println!("{}", hint),
+            None => println!("There are no more hints"),
+        },
+        ["next"] => match progress.next(EXERCISES) {
+            Some(exercise) => {
+                println!("{}\n", exercise.statement);
+                run(exercise, &mut progress);
             }
-            result
+            None => println!("All exercises are done"),
+        },
+        _ => {
+            println!("usage: exercises [list | show <name> | run <name> | hint <name> | next | reset]");
+            exit(2);
         }
-        let text = "first apple".to_string();
-        println!("Text: '{}', pigged: '{}'", text, to_pig_latin(&text));
-        let text = "съешь еще этих мягких французских булок".to_string();
-        println!("Text: '{}', pigged: '{}'", text, to_pig_latin(&text));
-        let text = "この　世界は　いいと　訝しまない".to_string(); // この　せかいは　いいと　いぶかしまない - vowel splitting does not work :(
-        println!("Text: '{}', pigged: '{}'", text, to_pig_latin(&text));
     }
-    {
-// Using a hash map and vectors, create a text interface to allow a user to add employee names
-// to a department in a company. For example, “Add Sally to Engineering” or “Add Amir to Sales.”
-// Then let the user retrieve a list of all people in a department or all people in the company
-// y department, sorted alphabetically.
+    if let Err(e) = progress.save() {
+        println!("Cannot save {}: {}", PROGRESS, e);
     }
 }
 
@@ -253,6 +503,29 @@ mod tests {
         check(" abc c-1 ", "%20abc%20c-1%20");
     }
 
+    #[test]
+    fn checks_pass_for_the_solutions_of_myrust() {
+        use myrust::{directory::Directory, pig_latin, stats};
+        assert_eq!(::check_mean(|n| stats::mean(n).ok()), Ok(()));
+        assert_eq!(::check_median(|n| stats::median(n).ok()), Ok(()));
+        assert_eq!(::check_modes(|n| stats::modes(n).unwrap_or_default()), Ok(()));
+        assert_eq!(::check_pig_latin(pig_latin::to_pig_latin, &["first apple"]), Ok(()));
+        assert_eq!(::check_company(|commands| {
+            let mut directory = Directory::new();
+            commands.iter().for_each(|c| { directory.execute(c).unwrap(); });
+            directory.execute("List all").unwrap()
+        }), Ok(()));
+        assert_eq!(::check_urlify(urlify), Ok(()));
+        assert_eq!(::check_unique_digits(generate_n_with_unq_dig), Ok(()));
+    }
+
+    #[test]
+    fn checks_fail_for_wrong_solutions() {
+        assert!(::check_mean(|n| n.first().map(|&x| x as f64)).is_err());
+        assert_eq!(::check_modes(|_| vec![2]), Err("[4, 1, 3, 2] gives [2], expected [1, 2, 3, 4]".to_string()));
+        assert!(::check_unique_digits(|a, b| (a..b).collect()).is_err());
+    }
+
     // tests for generate_n_with_unq_dig
     use generate_n_with_unq_dig;
 
@@ -270,6 +543,12 @@ mod tests {
         assert!(numbers.is_empty());
     }
 
+    #[test]
+    fn negative_values_diapason() {
+        let numbers = generate_n_with_unq_dig(-13, 3);
+        assert_eq!(vec![-13, -12, -10, -9, -8, -7, -6, -5, -4, -3, -2, -1, 0, 1, 2], numbers);
+    }
+
     #[test]
     fn many_values_diapason() {
         let numbers = generate_n_with_unq_dig(4000, 5000);
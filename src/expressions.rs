@@ -69,8 +69,9 @@ fn main() {
     let a = [1, 2, 3, 4];
     for x in a.iter() {
         println!("array value {}", x);
+        // x: &i32
         compilation_error!(
-            x = 1;  // x: &i32, expected `&{integer}`, found integer
+            x = 1;  // expected `&{integer}`, found integer
         );
     }
 
@@ -0,0 +1,377 @@
+// body of 'crate::front_of_house::hosting', declared with 'pub mod hosting;' in src/lib.rs
+// A host keeps a FIFO waitlist of parties and seats them at tables:
+//     let mut host = Host::new(&[2, 4, 4]);
+//     let party = host.add_to_waitlist(3, now)?;
+//     if let Some(seating) = host.seat_at_table(now) { ... } // the first party that fits a free table
+//     host.leave(seating.table, later)?;
+// Time is a 'Duration' since the restaurant opened, it is passed explicitly, so a day can be replayed.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+pub type PartyId = u32;
+pub type TableId = usize; // index in 'Host::tables()'
+
+// guests number tables from 1, messages and receipts have the number, not the 'TableId'
+pub fn table_number(table: TableId) -> usize {
+    table + 1
+}
+
+// None for 0, there is no such table
+pub fn table_id(number: usize) -> Option<TableId> {
+    number.checked_sub(1)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Party {
+    pub id: PartyId,
+    pub size: u32,
+    pub arrived: Duration,
+    pub reserved_for: Option<Duration>, // reserved parties are seated before walk-ins
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seating {
+    pub party: PartyId,
+    pub size: u32,
+    pub table: TableId,
+    pub seated: Duration,
+    pub waited: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub id: TableId,
+    pub capacity: u32,
+    pub seating: Option<Seating>, // None - the table is free
+    occupied: Duration, // by parties which already left
+}
+
+impl Table {
+    pub fn is_free(&self) -> bool {
+        self.seating.is_none()
+    }
+
+    // time the table was occupied until 'now'
+    pub fn occupied(&self, now: Duration) -> Duration {
+        self.occupied + self.seating.as_ref().map_or(Duration::ZERO, |s| now.saturating_sub(s.seated))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostingError {
+    EmptyParty,
+    PartyTooLarge { size: u32, largest: u32 },
+    UnknownParty(PartyId),
+    UnknownTable(TableId),
+    TableIsFree(TableId),
+}
+
+impl fmt::Display for HostingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HostingError::EmptyParty => write!(f, "a party can't be empty"),
+            HostingError::PartyTooLarge { size, largest } =>
+                write!(f, "no table for a party of {}, the largest table is for {}", size, largest),
+            HostingError::UnknownParty(id) => write!(f, "party {} is neither waiting nor reserved", id),
+            HostingError::UnknownTable(id) => write!(f, "there is no table {}", table_number(id)),
+            HostingError::TableIsFree(id) => write!(f, "table {} is free", table_number(id)),
+        }
+    }
+}
+
+impl std::error::Error for HostingError {}
+
+pub struct Host {
+    tables: Vec<Table>,
+    waitlist: VecDeque<Party>, // in order of arrival
+    reservations: Vec<Party>,  // not arrived yet
+    next_party: PartyId,
+    dining_time: Duration, // expected time a party stays at a table, until some parties have left
+    dined: (Duration, u32), // total time and number of parties which left
+}
+
+impl Host {
+    pub fn new(capacities: &[u32]) -> Host {
+        Host {
+            tables: capacities.iter().enumerate()
+                .map(|(id, &capacity)| Table { id, capacity, seating: None, occupied: Duration::ZERO })
+                .collect(),
+            waitlist: VecDeque::new(),
+            reservations: vec![],
+            next_party: 1,
+            dining_time: Duration::from_secs(45 * 60),
+            dined: (Duration::ZERO, 0),
+        }
+    }
+
+    pub fn set_dining_time(&mut self, dining_time: Duration) {
+        self.dining_time = dining_time;
+    }
+
+    // the guess given, or the average stay of parties which already left
+    pub fn dining_time(&self) -> Duration {
+        match self.dined {
+            (_, 0) => self.dining_time,
+            (total, parties) => total / parties,
+        }
+    }
+
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    pub fn reservations(&self) -> &[Party] {
+        &self.reservations
+    }
+
+    // waiting parties in the order they are going to be seated
+    pub fn waitlist(&self) -> Vec<&Party> {
+        self.queue().into_iter().map(|i| &self.waitlist[i]).collect()
+    }
+
+    pub fn add_to_waitlist(&mut self, size: u32, now: Duration) -> Result<PartyId, HostingError> {
+        let party = self.new_party(size, now, None)?;
+        let id = party.id;
+        self.waitlist.push_back(party);
+        Ok(id)
+    }
+
+    pub fn reserve(&mut self, size: u32, at: Duration) -> Result<PartyId, HostingError> {
+        let party = self.new_party(size, at, Some(at))?;
+        let id = party.id;
+        self.reservations.push(party);
+        Ok(id)
+    }
+
+    // a reserved party has come, it waits ahead of walk-ins
+    pub fn check_in(&mut self, id: PartyId, now: Duration) -> Result<(), HostingError> {
+        let i = self.reservations.iter().position(|p| p.id == id).ok_or(HostingError::UnknownParty(id))?;
+        let mut party = self.reservations.remove(i);
+        party.arrived = now;
+        self.waitlist.push_back(party);
+        Ok(())
+    }
+
+    // the party went away without being seated, or cancelled its reservation
+    pub fn cancel(&mut self, id: PartyId) -> Result<Party, HostingError> {
+        if let Some(i) = self.waitlist.iter().position(|p| p.id == id) {
+            return Ok(self.waitlist.remove(i).unwrap());
+        }
+        let i = self.reservations.iter().position(|p| p.id == id).ok_or(HostingError::UnknownParty(id))?;
+        Ok(self.reservations.remove(i))
+    }
+
+    // seats the first party in the queue which fits a free table, at the smallest such table
+    pub fn seat_at_table(&mut self, now: Duration) -> Option<Seating> {
+        for i in self.queue() {
+            let size = self.waitlist[i].size;
+            let table = self.tables.iter()
+                .filter(|t| t.is_free() && t.capacity >= size)
+                .min_by_key(|t| t.capacity)
+                .map(|t| t.id);
+            if let Some(table) = table {
+                let party = self.waitlist.remove(i).unwrap();
+                let seating = Seating {
+                    party: party.id,
+                    size: party.size,
+                    table,
+                    seated: now,
+                    waited: now.saturating_sub(party.arrived),
+                };
+                self.tables[table].seating = Some(seating.clone());
+                return Some(seating);
+            }
+        }
+        None
+    }
+
+    pub fn seat_all(&mut self, now: Duration) -> Vec<Seating> {
+        let mut seated = vec![];
+        while let Some(seating) = self.seat_at_table(now) {
+            seated.push(seating);
+        }
+        seated
+    }
+
+    // the party at the table has left, the table is free
+    pub fn leave(&mut self, table: TableId, now: Duration) -> Result<Seating, HostingError> {
+        let table = self.tables.get_mut(table).ok_or(HostingError::UnknownTable(table))?;
+        let seating = table.seating.take().ok_or(HostingError::TableIsFree(table.id))?;
+        let stay = now.saturating_sub(seating.seated);
+        table.occupied += stay;
+        self.dined.0 += stay;
+        self.dined.1 += 1;
+        Ok(seating)
+    }
+
+    // how long a waiting party is going to wait from 'now', if every party stays for 'dining_time()'
+    pub fn estimated_wait(&self, id: PartyId, now: Duration) -> Result<Duration, HostingError> {
+        self.estimate(now, None)
+            .into_iter()
+            .find(|&(party, _)| party == Some(id))
+            .map(|(_, wait)| wait)
+            .ok_or(HostingError::UnknownParty(id))
+    }
+
+    // how long a party of 'size' arriving now would wait
+    pub fn estimated_wait_for(&self, size: u32, now: Duration) -> Result<Duration, HostingError> {
+        self.check_size(size)?;
+        Ok(self.estimate(now, Some(size)).last().unwrap().1)
+    }
+
+    // share of the time since opening the tables were occupied, from 0.0 to 1.0
+    pub fn utilization(&self, now: Duration) -> f64 {
+        if self.tables.is_empty() || now.is_zero() {
+            return 0.0;
+        }
+        let occupied: Duration = self.tables.iter().map(|t| t.occupied(now)).sum();
+        occupied.as_secs_f64() / (now.as_secs_f64() * self.tables.len() as f64)
+    }
+
+    fn new_party(&mut self, size: u32, arrived: Duration, reserved_for: Option<Duration>) -> Result<Party, HostingError> {
+        self.check_size(size)?;
+        let id = self.next_party;
+        self.next_party += 1;
+        Ok(Party { id, size, arrived, reserved_for })
+    }
+
+    fn check_size(&self, size: u32) -> Result<(), HostingError> {
+        let largest = self.tables.iter().map(|t| t.capacity).max().unwrap_or(0);
+        if size == 0 {
+            Err(HostingError::EmptyParty)
+        } else if size > largest {
+            Err(HostingError::PartyTooLarge { size, largest })
+        } else {
+            Ok(())
+        }
+    }
+
+    // indexes of 'waitlist': reserved parties by time of reservation, then walk-ins by arrival
+    fn queue(&self) -> Vec<usize> {
+        let mut queue: Vec<usize> = (0..self.waitlist.len()).collect();
+        // sort is stable, walk-ins keep their order
+        queue.sort_by_key(|&i| match self.waitlist[i].reserved_for {
+            Some(at) => (0, at),
+            None => (1, Duration::ZERO),
+        });
+        queue
+    }
+
+    // replays the queue: every party takes the fitting table which gets free first
+    fn estimate(&self, now: Duration, newcomer: Option<u32>) -> Vec<(Option<PartyId>, Duration)> {
+        let dining_time = self.dining_time();
+        let mut free_at: Vec<Duration> = self.tables.iter()
+            .map(|t| t.seating.as_ref().map_or(now, |s| (s.seated + dining_time).max(now)))
+            .collect();
+        let parties = self.queue().into_iter()
+            .map(|i| (Some(self.waitlist[i].id), self.waitlist[i].size))
+            .chain(newcomer.map(|size| (None, size)));
+        let mut waits = vec![];
+        for (party, size) in parties {
+            let table = self.tables.iter()
+                .filter(|t| t.capacity >= size)
+                .min_by_key(|t| (free_at[t.id], t.capacity))
+                .map(|t| t.id);
+            if let Some(table) = table {
+                waits.push((party, free_at[table] - now));
+                free_at[table] += dining_time;
+            }
+        }
+        waits
+    }
+}
+
+// the host of 'front_of_house'
+pub fn add_to_waitlist(host: &mut Host, size: u32, now: Duration) -> Result<PartyId, HostingError> {
+    host.add_to_waitlist(size, now)
+}
+
+pub fn seat_at_table(host: &mut Host, now: Duration) -> Option<Seating> {
+    host.seat_at_table(now)
+}
+
+#[allow(unused)]
+fn prepare(host: &mut Host) { // private function, making module public does not affect on it's children
+    // relative path, relative to this module
+    add_to_waitlist(host, 2, Duration::ZERO).unwrap();
+    // relative path, relative to this module
+    self::seat_at_table(host, Duration::ZERO);
+    // path, relative to this module
+    self::super::look_at_the_personal();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(m: u64) -> Duration {
+        Duration::from_secs(m * 60)
+    }
+
+    #[test]
+    fn seats_the_first_party_that_fits() {
+        let mut host = Host::new(&[2, 4]);
+        let four = host.add_to_waitlist(4, minutes(0)).unwrap();
+        let two = host.add_to_waitlist(2, minutes(1)).unwrap();
+        let three = host.add_to_waitlist(3, minutes(2)).unwrap();
+        let seated = host.seat_all(minutes(5));
+        assert_eq!(seated.iter().map(|s| (s.party, s.table)).collect::<Vec<_>>(), vec![(four, 1), (two, 0)]);
+        assert_eq!(seated[1].waited, minutes(4));
+        assert_eq!(host.waitlist().iter().map(|p| p.id).collect::<Vec<_>>(), vec![three]);
+        assert_eq!(host.seat_at_table(minutes(6)), None);
+        host.leave(1, minutes(50)).unwrap();
+        assert_eq!(host.seat_at_table(minutes(50)).map(|s| s.party), Some(three));
+    }
+
+    #[test]
+    fn reservations_take_priority() {
+        let mut host = Host::new(&[4]);
+        let walk_in = host.add_to_waitlist(2, minutes(0)).unwrap();
+        let late = host.reserve(4, minutes(30)).unwrap();
+        let early = host.reserve(3, minutes(10)).unwrap();
+        host.check_in(late, minutes(1)).unwrap();
+        host.check_in(early, minutes(2)).unwrap();
+        assert_eq!(host.check_in(early, minutes(2)), Err(HostingError::UnknownParty(early)));
+        assert_eq!(host.waitlist().iter().map(|p| p.id).collect::<Vec<_>>(), vec![early, late, walk_in]);
+        assert_eq!(host.seat_at_table(minutes(3)).unwrap().party, early);
+    }
+
+    #[test]
+    fn estimates_waits_by_dining_time() {
+        let mut host = Host::new(&[2, 4]);
+        host.set_dining_time(minutes(40));
+        host.add_to_waitlist(4, minutes(0)).unwrap();
+        host.add_to_waitlist(2, minutes(0)).unwrap();
+        host.seat_all(minutes(0));
+        let a = host.add_to_waitlist(3, minutes(10)).unwrap();
+        let b = host.add_to_waitlist(2, minutes(10)).unwrap();
+        assert_eq!(host.estimated_wait(a, minutes(10)), Ok(minutes(30)));
+        assert_eq!(host.estimated_wait(b, minutes(10)), Ok(minutes(30)));
+        assert_eq!(host.estimated_wait_for(4, minutes(10)), Ok(minutes(70)));
+        // parties which left tell the real dining time
+        host.leave(0, minutes(20)).unwrap();
+        assert_eq!(host.dining_time(), minutes(20));
+    }
+
+    #[test]
+    fn utilization_is_share_of_occupied_table_time() {
+        let mut host = Host::new(&[2, 2]);
+        assert_eq!(host.utilization(minutes(0)), 0.0);
+        host.add_to_waitlist(2, minutes(0)).unwrap();
+        host.seat_all(minutes(0));
+        host.leave(0, minutes(30)).unwrap();
+        assert_eq!(host.utilization(minutes(60)), 0.25);
+        assert_eq!(host.tables()[0].occupied(minutes(60)), minutes(30));
+    }
+
+    #[test]
+    fn rejects_parties_without_table() {
+        let mut host = Host::new(&[2, 4]);
+        assert_eq!(host.add_to_waitlist(0, minutes(0)), Err(HostingError::EmptyParty));
+        assert_eq!(host.reserve(5, minutes(0)), Err(HostingError::PartyTooLarge { size: 5, largest: 4 }));
+        assert_eq!(host.leave(1, minutes(0)), Err(HostingError::TableIsFree(1)));
+        assert_eq!(host.leave(7, minutes(0)).unwrap_err().to_string(), "there is no table 8");
+    }
+}
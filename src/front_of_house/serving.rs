@@ -0,0 +1,395 @@
+// body of 'crate::front_of_house::serving', declared with 'pub mod serving;' in src/lib.rs
+// An order goes Placed -> Cooking -> Served -> Paid, any other transition is an error:
+//     let mut waiter = Waiter::new(Menu::house());
+//     let order = waiter.take_order(3, &["Rye", "Soup"])?;
+//     waiter.start_cooking(order)?;
+//     waiter.serve_order(order)?;
+//     let bill = waiter.take_payment(order, Tip::Percent(15))?;
+//     println!("{}", bill); // the receipt
+// Money is in cents, taxes are per item in basis points (800 is 8%).
+
+use std::collections::BTreeMap;
+use std::fmt;
+use back_of_house::{Appetizer, Breakfast};
+use front_of_house::hosting::{table_number, TableId};
+
+pub type Cents = u64;
+pub type OrderId = u32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dish {
+    Breakfast(Breakfast),
+    Appetizer(Appetizer),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuItem {
+    pub name: String,
+    pub dish: Dish,
+    pub price: Cents,
+    pub tax_rate: u32, // basis points
+}
+
+impl MenuItem {
+    pub fn tax(&self) -> Cents {
+        // rounded half up
+        (self.price * self.tax_rate as u64 + 5_000) / 10_000
+    }
+}
+
+pub struct Menu {
+    items: Vec<MenuItem>,
+}
+
+impl Menu {
+    pub fn new() -> Menu {
+        Menu { items: vec![] }
+    }
+
+    // what back_of_house can cook
+    pub fn house() -> Menu {
+        let mut menu = Menu::new();
+        menu.add("Rye", Dish::Breakfast(Breakfast::summer("Rye")), 650, 800);
+        menu.add("Wheat", Dish::Breakfast(Breakfast::summer("Wheat")), 600, 800);
+        menu.add("Soup", Dish::Appetizer(Appetizer::Soup), 450, 800);
+        menu.add("Salad", Dish::Appetizer(Appetizer::Salad), 500, 500);
+        menu
+    }
+
+    pub fn add(&mut self, name: &str, dish: Dish, price: Cents, tax_rate: u32) {
+        self.items.push(MenuItem { name: name.to_string(), dish, price, tax_rate });
+    }
+
+    pub fn items(&self) -> &[MenuItem] {
+        &self.items
+    }
+
+    // names are case-insensitive
+    pub fn find(&self, name: &str) -> Option<&MenuItem> {
+        self.items.iter().find(|i| i.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Default for Menu {
+    fn default() -> Menu {
+        Menu::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    Placed,
+    Cooking,
+    Served,
+    Paid,
+}
+
+impl fmt::Display for OrderState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            OrderState::Placed => "placed",
+            OrderState::Cooking => "cooking",
+            OrderState::Served => "served",
+            OrderState::Paid => "paid",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    EmptyOrder,
+    UnknownItem(String),
+    UnknownOrder(OrderId),
+    InvalidTransition { order: OrderId, from: OrderState, to: OrderState },
+    InvalidSplit(String),
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OrderError::EmptyOrder => write!(f, "an order must have at least one item"),
+            OrderError::UnknownItem(ref name) => write!(f, "there is no '{}' in the menu", name),
+            OrderError::UnknownOrder(id) => write!(f, "there is no order {}", id),
+            OrderError::InvalidTransition { order, from, to } =>
+                write!(f, "order {} is {}, it can't become {}", order, from, to),
+            OrderError::InvalidSplit(ref reason) => write!(f, "can't split the bill: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    pub id: OrderId,
+    pub table: TableId,
+    pub items: Vec<MenuItem>,
+    state: OrderState,
+    bill: Option<Bill>, // when paid
+}
+
+impl Order {
+    pub fn state(&self) -> OrderState {
+        self.state
+    }
+
+    pub fn bill(&self) -> Option<&Bill> {
+        self.bill.as_ref()
+    }
+
+    // the bill as it would be with the tip, the order is not paid
+    pub fn make_bill(&self, tip: Tip) -> Bill {
+        let lines: Vec<BillLine> = self.items.iter()
+            .map(|i| BillLine { name: i.name.clone(), price: i.price, tax: i.tax() })
+            .collect();
+        let subtotal: Cents = lines.iter().map(|l| l.price).sum();
+        let tip = match tip {
+            Tip::Nothing => 0,
+            Tip::Percent(p) => (subtotal * p as u64 + 50) / 100,
+            Tip::Amount(amount) => amount,
+        };
+        Bill { order: self.id, table: self.table, lines, tip }
+    }
+
+    fn advance(&mut self, to: OrderState) -> Result<(), OrderError> {
+        let allowed = matches!(
+            (self.state, to),
+            (OrderState::Placed, OrderState::Cooking) |
+            (OrderState::Cooking, OrderState::Served) |
+            (OrderState::Served, OrderState::Paid)
+        );
+        if allowed {
+            self.state = to;
+            Ok(())
+        } else {
+            Err(OrderError::InvalidTransition { order: self.id, from: self.state, to })
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tip {
+    Nothing,
+    Percent(u32), // of the subtotal, without taxes
+    Amount(Cents),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BillLine {
+    pub name: String,
+    pub price: Cents,
+    pub tax: Cents,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bill {
+    pub order: OrderId,
+    pub table: TableId,
+    pub lines: Vec<BillLine>,
+    pub tip: Cents,
+}
+
+impl Bill {
+    pub fn subtotal(&self) -> Cents {
+        self.lines.iter().map(|l| l.price).sum()
+    }
+
+    pub fn tax(&self) -> Cents {
+        self.lines.iter().map(|l| l.tax).sum()
+    }
+
+    pub fn total(&self) -> Cents {
+        self.subtotal() + self.tax() + self.tip
+    }
+
+    // equal shares, the cents left are paid by the first guests
+    pub fn split_evenly(&self, guests: u32) -> Result<Vec<Cents>, OrderError> {
+        if guests == 0 {
+            return Err(OrderError::InvalidSplit("nobody to pay".to_string()));
+        }
+        let total = self.total();
+        let (share, left) = (total / guests as u64, total % guests as u64);
+        Ok((0..guests as u64).map(|g| share + if g < left { 1 } else { 0 }).collect())
+    }
+
+    // every guest pays for own items with their taxes, the tip is shared in proportion to prices
+    // 'guests[g]' are indexes of lines paid by the guest 'g', each line must be paid once
+    pub fn split_by_items(&self, guests: &[Vec<usize>]) -> Result<Vec<Cents>, OrderError> {
+        let mut paid = vec![false; self.lines.len()];
+        for &line in guests.iter().flatten() {
+            match paid.get_mut(line) {
+                None => return Err(OrderError::InvalidSplit(format!("there is no line {}", line))),
+                Some(&mut true) => return Err(OrderError::InvalidSplit(format!("line {} is paid twice", line))),
+                Some(p) => *p = true,
+            }
+        }
+        if let Some(line) = paid.iter().position(|&p| !p) {
+            return Err(OrderError::InvalidSplit(format!("nobody pays for line {}", line)));
+        }
+        let subtotal = self.subtotal();
+        let mut shares: Vec<Cents> = guests.iter()
+            .map(|lines| {
+                let (price, tax) = lines.iter().fold((0, 0), |(p, t), &l| (p + self.lines[l].price, t + self.lines[l].tax));
+                let tip = (self.tip * price).checked_div(subtotal).unwrap_or(0);
+                price + tax + tip
+            })
+            .collect();
+        // the tip rounded down, the rest goes to the first guest
+        let left = self.total() - shares.iter().sum::<Cents>();
+        if let Some(first) = shares.first_mut() {
+            *first += left;
+        }
+        Ok(shares)
+    }
+}
+
+pub fn money(cents: Cents) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+// the receipt
+impl fmt::Display for Bill {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Order {}, table {}", self.order, table_number(self.table))?;
+        for line in &self.lines {
+            writeln!(f, "  {:<22}{:>10}", line.name, money(line.price))?;
+        }
+        writeln!(f, "{:<24}{:>10}", "Subtotal", money(self.subtotal()))?;
+        writeln!(f, "{:<24}{:>10}", "Tax", money(self.tax()))?;
+        writeln!(f, "{:<24}{:>10}", "Tip", money(self.tip))?;
+        write!(f, "{:<24}{:>10}", "Total", money(self.total()))
+    }
+}
+
+// takes orders of the tables and moves them through their states
+pub struct Waiter {
+    menu: Menu,
+    orders: BTreeMap<OrderId, Order>,
+    next_order: OrderId,
+}
+
+impl Waiter {
+    pub fn new(menu: Menu) -> Waiter {
+        Waiter { menu, orders: BTreeMap::new(), next_order: 1 }
+    }
+
+    pub fn menu(&self) -> &Menu {
+        &self.menu
+    }
+
+    pub fn order(&self, id: OrderId) -> Result<&Order, OrderError> {
+        self.orders.get(&id).ok_or(OrderError::UnknownOrder(id))
+    }
+
+    // in order of ids
+    pub fn orders(&self) -> impl Iterator<Item = &Order> {
+        self.orders.values()
+    }
+
+    pub fn take_order(&mut self, table: TableId, items: &[&str]) -> Result<OrderId, OrderError> {
+        if items.is_empty() {
+            return Err(OrderError::EmptyOrder);
+        }
+        let items = items.iter()
+            .map(|name| self.menu.find(name).cloned().ok_or_else(|| OrderError::UnknownItem(name.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+        let id = self.next_order;
+        self.next_order += 1;
+        self.orders.insert(id, Order { id, table, items, state: OrderState::Placed, bill: None });
+        Ok(id)
+    }
+
+    pub fn start_cooking(&mut self, id: OrderId) -> Result<(), OrderError> {
+        self.order_mut(id)?.advance(OrderState::Cooking)
+    }
+
+    pub fn serve_order(&mut self, id: OrderId) -> Result<(), OrderError> {
+        self.order_mut(id)?.advance(OrderState::Served)
+    }
+
+    pub fn take_payment(&mut self, id: OrderId, tip: Tip) -> Result<Bill, OrderError> {
+        let order = self.order_mut(id)?;
+        order.advance(OrderState::Paid)?;
+        let bill = order.make_bill(tip);
+        order.bill = Some(bill.clone());
+        Ok(bill)
+    }
+
+    fn order_mut(&mut self, id: OrderId) -> Result<&mut Order, OrderError> {
+        self.orders.get_mut(&id).ok_or(OrderError::UnknownOrder(id))
+    }
+}
+
+pub fn take_order(waiter: &mut Waiter, table: TableId, items: &[&str]) -> Result<OrderId, OrderError> {
+    // path, relative to this module
+    super::look_at_the_personal();
+    waiter.take_order(table, items)
+}
+
+pub fn serve_order(waiter: &mut Waiter, id: OrderId) -> Result<(), OrderError> {
+    waiter.serve_order(id)
+}
+
+pub fn take_payment(waiter: &mut Waiter, id: OrderId, tip: Tip) -> Result<Bill, OrderError> {
+    waiter.take_payment(id, tip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn served(items: &[&str]) -> (Waiter, OrderId) {
+        let mut waiter = Waiter::new(Menu::house());
+        let order = waiter.take_order(2, items).unwrap();
+        waiter.start_cooking(order).unwrap();
+        waiter.serve_order(order).unwrap();
+        (waiter, order)
+    }
+
+    #[test]
+    fn order_goes_through_states_in_order() {
+        let mut waiter = Waiter::new(Menu::house());
+        let order = waiter.take_order(1, &["rye", "Salad"]).unwrap();
+        assert_eq!(waiter.order(order).unwrap().state(), OrderState::Placed);
+        assert_eq!(
+            waiter.serve_order(order),
+            Err(OrderError::InvalidTransition { order, from: OrderState::Placed, to: OrderState::Served })
+        );
+        waiter.start_cooking(order).unwrap();
+        assert!(waiter.take_payment(order, Tip::Nothing).is_err());
+        waiter.serve_order(order).unwrap();
+        waiter.take_payment(order, Tip::Nothing).unwrap();
+        assert_eq!(waiter.order(order).unwrap().state(), OrderState::Paid);
+        assert_eq!(waiter.start_cooking(order).unwrap_err().to_string(), "order 1 is paid, it can't become cooking");
+        assert_eq!(waiter.take_order(1, &["Pizza"]), Err(OrderError::UnknownItem("Pizza".to_string())));
+        assert_eq!(waiter.take_order(1, &[]), Err(OrderError::EmptyOrder));
+    }
+
+    #[test]
+    fn bill_has_item_taxes_and_tip() {
+        let (mut waiter, order) = served(&["Rye", "Soup", "Salad"]);
+        let bill = waiter.take_payment(order, Tip::Percent(15)).unwrap();
+        assert_eq!(bill.subtotal(), 1600);
+        assert_eq!(bill.tax(), 52 + 36 + 25);
+        assert_eq!(bill.tip, 240);
+        assert_eq!(bill.total(), 1953);
+        assert_eq!(waiter.order(order).unwrap().bill(), Some(&bill));
+        let receipt = bill.to_string();
+        assert!(receipt.starts_with("Order 1, table 3\n  Rye "));
+        assert!(receipt.ends_with("Total                        19.53"));
+    }
+
+    #[test]
+    fn split_bills() {
+        let (waiter, order) = served(&["Rye", "Soup", "Salad"]);
+        let bill = waiter.order(order).unwrap().make_bill(Tip::Amount(100));
+        assert_eq!(bill.split_evenly(3), Ok(vec![605, 604, 604]));
+        let shares = bill.split_by_items(&[vec![0], vec![1, 2]]).unwrap();
+        assert_eq!(shares.iter().sum::<Cents>(), bill.total());
+        assert_eq!(shares, vec![650 + 52 + 41, 950 + 61 + 59]);
+        assert!(bill.split_by_items(&[vec![0, 1]]).is_err());
+        assert!(bill.split_by_items(&[vec![0, 1, 2], vec![2]]).is_err());
+        assert!(bill.split_evenly(0).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 extern crate myrust;
 use self::myrust::compilation_error;
+use self::myrust::memoize;
 
 fn with_param(x: i32) {
     println!("Passed integer value is {}", x);
@@ -9,8 +10,9 @@ fn with_param(x: i32) {
 }
 
 fn return_something() -> i32 {
+    // let construction is not an expression, it does not return anything
     compilation_error!(
-        let x = 2 // let construction is not an expression, it does not return anything
+        let x = 2 // expected `;`, found keyword `let`
     );
     let x = 2; x // 'x' is an expression, this function returns x's value
     // 'x' on this new line is also OK
@@ -22,10 +24,30 @@ fn return_something_else() -> i32 {
 }
 
 ///     0 | 1 | 1 | 2 | 3 | 5 | 8 | 13 | 21
-fn fibonacci(n: i32) -> i32 {
+// '#[memoize]' caches results, recursive calls hit the cache, so fibonacci(90) takes 91 calculations instead of 10^19
+#[memoize]
+fn fibonacci(n: u32) -> u64 {
     if n == 0 { 0 } else if n == 1 { 1 } else { fibonacci(n - 1) + fibonacci(n-2) }
 }
 
+compilation_error!(
+    #[memoize(capacity = 0)] fn identity(n: u32) -> u32 { n } // error: the capacity must be at least 1
+);
+
+compilation_error!(
+    #[memoize] fn length(s: &str) -> usize { s.len() } // error: a borrowed argument can not be memoized
+);
+
+compilation_error!(
+    #[memoize] fn words(text: std::borrow::Cow<'static, str>) -> usize { text.split(' ').count() } // error: an argument with a lifetime can not be memoized
+);
+
+// one cache for all threads, it keeps 100 values
+#[memoize(scope = global, capacity = 100)]
+fn collatz_steps(n: u64) -> u32 {
+    if n == 1 { 0 } else if n.is_multiple_of(2) { 1 + collatz_steps(n / 2) } else { 1 + collatz_steps(3 * n + 1) }
+}
+
 fn main() {
     // rust support forward definition
     a_function();
@@ -51,6 +73,9 @@ fn main() {
     );
 
     println!("fibonacci(6) is {}", fibonacci(6));
+    println!("fibonacci(90) is {}", fibonacci(90));
+    fibonacci::clear_cache(); // the cache of this thread
+    println!("27 takes {} steps to 1, {:?}", collatz_steps(27), collatz_steps::stats());
 }
 
 // returns nothing... '()'
@@ -61,3 +86,49 @@ fn a_function() {
         inner_function(); // cannot find function `inner_function` in this scope
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn recursive_calls_are_cached() {
+        fibonacci::clear_cache();
+        assert_eq!(fibonacci(90), 2_880_067_194_370_816_120);
+        assert_eq!(fibonacci::stats().misses, 91); // every n from 0 to 90 once
+        assert_eq!(fibonacci(6), 8);
+        assert_eq!(fibonacci::stats().misses, 91);
+        fibonacci::clear_cache();
+        assert_eq!(fibonacci(6), 8);
+        assert_eq!(fibonacci::stats().entries, 7);
+        // another thread has its own cache
+        thread::spawn(|| assert_eq!(fibonacci::stats().entries, 0)).join().unwrap();
+    }
+
+    #[test]
+    fn global_cache_is_shared_and_bounded() {
+        collatz_steps::clear_cache();
+        thread::spawn(|| assert_eq!(collatz_steps(27), 111)).join().unwrap();
+        let misses = collatz_steps::stats().misses;
+        assert_eq!(collatz_steps(27), 111);
+        assert_eq!(collatz_steps::stats().misses, misses);
+        assert_eq!(collatz_steps::stats().entries, 100); // of 112 numbers on the way
+    }
+
+    fn calculate(n: u32) -> u32 {
+        n * 10
+    }
+
+    #[memoize]
+    fn calculate_and_add_one(n: u32) -> u32 {
+        calculate(n) + 1 // this module's 'calculate', the expansion does not hide it
+    }
+
+    #[test]
+    fn memoized_body_calls_functions_around_it() {
+        assert_eq!(calculate_and_add_one(2), 21);
+        assert_eq!(calculate_and_add_one(2), 21);
+        assert_eq!(calculate_and_add_one::stats().hits, 1);
+    }
+}
@@ -1,33 +1,10 @@
+extern crate myrust;
+use myrust::Tracked;
 use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
 
 // for types with don't impl Copy trait keys/values will be moved to HashMap
 // HashMap will be owner of thous keys/values
 
-#[derive(Debug)]
-struct Verbose {
-    id: i32
-}
-
-impl Drop for Verbose{
-    fn drop(&mut self) {
-        println!("Dropping Verbose{{ id: {} }}", self.id);
-    }
-}
-
-impl PartialEq for Verbose {
-    fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
-    }
-}
-impl Eq for Verbose {}
-
-impl Hash for Verbose {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.id.hash(state)
-    }
-}
-
 fn main() {
     {
         let mut scores = HashMap::new(); // an empty hash map
@@ -69,13 +46,14 @@ fn main() {
     }
     {
         let mut hm = HashMap::new();
-        hm.insert("key", Verbose{ id: 1 });
+        hm.insert("key", Tracked::labeled("value", String::from("Blue")).moved_into("HashMap"));
         println!("Hash map with verbose is {:?}", hm);
         // value is dropped after scope ends
     }
     {
         let mut hm = HashMap::new();
-        hm.insert(Verbose{ id: 1 }, 1); // key is moved to hash map
+        hm.insert(Tracked::labeled("key", String::from("Yellow")).moved_into("HashMap"), 1); // key is moved to hash map
+        println!("Value by key Yellow is {:?}", hm.get("Yellow")); // Tracked<String> is hashed and compared as String
         println!("Hash map with verbose is {:?}", hm);
         // key is dropped after scope ends
     }
@@ -3,6 +3,8 @@ extern crate rand;
 
 use myrust::compilation_error;
 use myrust::Verbose;
+#[cfg(test)]
+use myrust::journal;
 use rand::Rng;
 
 fn main() {
@@ -32,9 +34,9 @@ fn main() {
     {
         // ownership taking iterator: into_iter
         let verboses = vec![
-            Verbose { id: 1 },
-            Verbose { id: 2 },
-            Verbose { id: 3 }
+            Verbose::new(1),
+            Verbose::new(2),
+            Verbose::new(3)
         ];
         let it = verboses.into_iter();
         for x in it { // x is a variable that got ownership
@@ -67,9 +69,9 @@ fn main() {
         println!("Sum of elements {:?} is {}", v, sum_of_elements);
         assert_eq!(6, sum_of_elements);
 
-        // iterator is no longer valid
+        // iterator is no longer valid, `it` moved due to 'it.sum()' method call
         compilation_error!(
-            for x in it {} // use of moved value: `it`, `it` moved due to 'it.sum()' method call
+            for x in it {} // use of moved value: `it`, `it` moved due to this method call
         );
     }
     {
@@ -144,4 +146,15 @@ fn using_other_iterator_trait_methods() {
         .filter(|x| x % 3 == 0)
         .reduce(|x, y| x + y);
     assert_eq!(Some(126), r);
-}
\ No newline at end of file
+}
+
+#[test]
+fn into_iter_drops_each_element_at_the_end_of_its_iteration() {
+    let recording = journal::start();
+    let verboses = vec![Verbose::new(1), Verbose::new(2), Verbose::new(3)];
+    for x in verboses {
+        recording.assert_drops(&(1..x.id).collect::<Vec<_>>()); // all previous elements are already dropped
+        recording.assert_not_dropped(x.id);
+    }
+    recording.assert_drops(&[1, 2, 3]);
+}
@@ -0,0 +1,186 @@
+// journal of 'Verbose' lifecycle: every construction, clone and drop, from all threads of the process.
+// Tests check drop order with it instead of reading what was printed:
+//     let recording = journal::start();
+//     { let a = Verbose::new(5); let b = Verbose::new(6); }
+//     recording.assert_drops(&[6, 5]);
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Created,
+    Cloned { from: u64 }, // instance of the original, 0 if it was not recorded
+    Dropped,
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub seq: u64, // order of events across all threads
+    pub id: i32,
+    pub instance: u64,
+    pub kind: EventKind,
+    pub thread: ThreadId,
+    pub thread_name: Option<String>,
+}
+
+struct Journal {
+    next_seq: u64,
+    events: Vec<Event>,
+}
+
+static JOURNAL: Mutex<Journal> = Mutex::new(Journal { next_seq: 0, events: Vec::new() });
+static NEXT_INSTANCE: AtomicU64 = AtomicU64::new(1); // 0 is for values which are not recorded
+static PRINTING: AtomicBool = AtomicBool::new(true);
+
+pub fn next_instance() -> u64 {
+    NEXT_INSTANCE.fetch_add(1, Ordering::Relaxed)
+}
+
+pub fn record(id: i32, instance: u64, kind: EventKind) {
+    let current = thread::current();
+    // a panicking test must not break the journal for other tests
+    let mut journal = JOURNAL.lock().unwrap_or_else(|e| e.into_inner());
+    let seq = journal.next_seq;
+    journal.next_seq += 1;
+    journal.events.push(Event {
+        seq,
+        id,
+        instance,
+        kind,
+        thread: current.id(),
+        thread_name: current.name().map(String::from),
+    });
+}
+
+// events recorded since 'from_seq'
+fn events_since(from_seq: u64) -> Vec<Event> {
+    let journal = JOURNAL.lock().unwrap_or_else(|e| e.into_inner());
+    journal.events.iter().filter(|e| e.seq >= from_seq).cloned().collect()
+}
+
+pub fn events() -> Vec<Event> {
+    events_since(0)
+}
+
+pub fn clear() {
+    JOURNAL.lock().unwrap_or_else(|e| e.into_inner()).events.clear();
+}
+
+// "Dropping Verbose { id: 1 }" is printed by default
+pub fn set_printing(enabled: bool) {
+    PRINTING.store(enabled, Ordering::Relaxed);
+}
+
+pub fn printing() -> bool {
+    PRINTING.load(Ordering::Relaxed)
+}
+
+// starts to look at the journal from now on
+pub fn start() -> Recording {
+    let journal = JOURNAL.lock().unwrap_or_else(|e| e.into_inner());
+    Recording { from_seq: journal.next_seq }
+}
+
+// Tests run in parallel and share the journal, so a recording sees events of other tests too.
+// Assertions look only at ids they are given, use ids which are unique for a test.
+pub struct Recording {
+    from_seq: u64,
+}
+
+impl Recording {
+    pub fn events(&self) -> Vec<Event> {
+        events_since(self.from_seq)
+    }
+
+    pub fn events_of(&self, id: i32) -> Vec<Event> {
+        self.events().into_iter().filter(|e| e.id == id).collect()
+    }
+
+    // ids of dropped values in order of dropping
+    pub fn drops(&self) -> Vec<i32> {
+        self.events().iter().filter(|e| e.kind == EventKind::Dropped).map(|e| e.id).collect()
+    }
+
+    // drops of values with ids from 'expected' happened exactly in this order
+    pub fn assert_drops(&self, expected: &[i32]) {
+        let actual: Vec<i32> = self.drops().into_iter().filter(|id| expected.contains(id)).collect();
+        assert_eq!(actual, expected, "Verbose values were dropped in other order");
+    }
+
+    // thread which dropped the value with 'id', the last one, if there were many
+    pub fn dropped_on(&self, id: i32) -> Option<ThreadId> {
+        self.events_of(id).iter().rev().find(|e| e.kind == EventKind::Dropped).map(|e| e.thread)
+    }
+
+    pub fn assert_dropped_on(&self, id: i32, thread: ThreadId) {
+        match self.dropped_on(id) {
+            Some(t) => assert_eq!(t, thread, "Verbose {{ id: {} }} was dropped on other thread", id),
+            None => panic!("Verbose {{ id: {} }} was not dropped", id),
+        }
+    }
+
+    pub fn assert_not_dropped(&self, id: i32) {
+        assert_eq!(self.dropped_on(id), None, "Verbose {{ id: {} }} was dropped", id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Verbose;
+
+    #[test]
+    fn records_creation_clone_and_drop() {
+        let recording = start();
+        let v = Verbose::new(-1);
+        let c = v.clone();
+        drop(c);
+        drop(v);
+        let kinds: Vec<(u64, EventKind)> = recording.events_of(-1).iter().map(|e| (e.instance, e.kind)).collect();
+        let (original, copy) = (kinds[0].0, kinds[1].0);
+        assert_ne!(original, copy);
+        assert_eq!(kinds, vec![
+            (original, EventKind::Created),
+            (copy, EventKind::Cloned { from: original }),
+            (copy, EventKind::Dropped),
+            (original, EventKind::Dropped),
+        ]);
+    }
+
+    #[test]
+    fn a_value_is_its_own_instance_whatever_its_id() {
+        let recording = start();
+        let a = Verbose::new(-6);
+        let mut b = Verbose::new(-6);
+        b.id = -7;
+        drop(b);
+        drop(Verbose { id: -6, instance: 0 }); // not recorded
+        let a_instance = recording.events_of(-6)[0].instance;
+        drop(a);
+        let kinds: Vec<(i32, EventKind)> = recording.events().iter()
+            .filter(|e| e.id == -6 || e.id == -7)
+            .map(|e| (e.id, e.kind))
+            .collect();
+        assert_eq!(kinds, vec![(-6, EventKind::Created), (-6, EventKind::Created), (-7, EventKind::Dropped), (-6, EventKind::Dropped)]);
+        assert_eq!(recording.events_of(-6).last().unwrap().instance, a_instance);
+    }
+
+    #[test]
+    fn drop_order_and_thread() {
+        let recording = start();
+        let v = Verbose::new(-2);
+        let t = thread::spawn(move || drop(v));
+        let spawned = t.thread().id();
+        t.join().unwrap();
+        {
+            let _a = Verbose::new(-3);
+            let _b = Verbose::new(-4);
+        }
+        recording.assert_drops(&[-2, -4, -3]);
+        recording.assert_dropped_on(-2, spawned);
+        recording.assert_dropped_on(-3, thread::current().id());
+        recording.assert_not_dropped(-5);
+    }
+}
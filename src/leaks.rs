@@ -0,0 +1,167 @@
+// registry of 'Verbose' values which are alive right now, and a guard which finds the ones leaked in its scope:
+//     let guard = LeakGuard::new();
+//     make_rc_cycle();
+//     guard.assert_leaks(&[5, 10]);
+// a guard that is just dropped prints its leaks to stderr, put one at the top of 'main' to see leaks at exit
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::panic::Location;
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+use journal;
+
+#[derive(Debug, Clone)]
+pub struct Leak {
+    pub id: i32, // id at creation time
+    pub instance: u64,
+    pub location: &'static Location<'static>,
+    pub thread: ThreadId,
+}
+
+impl fmt::Display for Leak {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Verbose {{ id: {} }} created at {}", self.id, self.location)
+    }
+}
+
+static LIVE: Mutex<BTreeMap<u64, Leak>> = Mutex::new(BTreeMap::new());
+
+fn live() -> std::sync::MutexGuard<'static, BTreeMap<u64, Leak>> {
+    LIVE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn register(id: i32, instance: u64, location: &'static Location<'static>) {
+    live().insert(instance, Leak { id, instance, location, thread: thread::current().id() });
+}
+
+pub fn unregister(instance: u64) {
+    live().remove(&instance);
+}
+
+// all values alive now, in order of creation
+pub fn alive() -> Vec<Leak> {
+    live().values().cloned().collect()
+}
+
+pub struct LeakGuard {
+    from_instance: u64,
+    thread: Option<ThreadId>, // None - values created by any thread
+    location: &'static Location<'static>,
+    checked: bool,
+}
+
+impl LeakGuard {
+    // looks at values created by this thread, tests running in parallel do not disturb each other
+    #[track_caller]
+    pub fn new() -> LeakGuard {
+        LeakGuard::start(Some(thread::current().id()))
+    }
+
+    // looks at values created by any thread after the guard
+    #[track_caller]
+    pub fn all_threads() -> LeakGuard {
+        LeakGuard::start(None)
+    }
+
+    #[track_caller]
+    fn start(thread: Option<ThreadId>) -> LeakGuard {
+        LeakGuard { from_instance: journal::next_instance(), thread, location: Location::caller(), checked: false }
+    }
+
+    // values created in the scope of the guard, which are still alive
+    pub fn leaks(&self) -> Vec<Leak> {
+        live()
+            .range(self.from_instance..)
+            .map(|(_, leak)| leak)
+            .filter(|leak| self.thread.is_none_or(|t| t == leak.thread))
+            .cloned()
+            .collect()
+    }
+
+    // ends the scope without printing
+    pub fn finish(mut self) -> Vec<Leak> {
+        self.checked = true;
+        self.leaks()
+    }
+
+    pub fn assert_no_leaks(self) {
+        let leaks = self.finish();
+        assert!(leaks.is_empty(), "memory leak: {}", describe(&leaks));
+    }
+
+    // exactly values with these ids leaked, in any order
+    pub fn assert_leaks(self, ids: &[i32]) {
+        let leaks = self.finish();
+        let mut actual: Vec<i32> = leaks.iter().map(|l| l.id).collect();
+        let mut expected = ids.to_vec();
+        actual.sort();
+        expected.sort();
+        assert_eq!(actual, expected, "leaked: {}", describe(&leaks));
+    }
+}
+
+impl Default for LeakGuard {
+    #[track_caller]
+    fn default() -> LeakGuard {
+        LeakGuard::new()
+    }
+}
+
+impl Drop for LeakGuard {
+    fn drop(&mut self) {
+        if self.checked {
+            return;
+        }
+        let leaks = self.leaks();
+        if !leaks.is_empty() {
+            eprintln!("{} Verbose values created in scope of LeakGuard at {} were not dropped:", leaks.len(), self.location);
+            for leak in leaks {
+                eprintln!("    {}", leak);
+            }
+        }
+    }
+}
+
+fn describe(leaks: &[Leak]) -> String {
+    leaks.iter().map(|l| l.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem;
+    use Verbose;
+
+    #[test]
+    fn reports_values_alive_at_the_end_of_scope() {
+        let guard = LeakGuard::new();
+        let dropped = Verbose::new(-10);
+        let forgotten = Verbose::new(-11);
+        let line = line!() - 1;
+        drop(dropped);
+        mem::forget(forgotten);
+        let leaks = guard.finish();
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].id, -11);
+        assert_eq!((leaks[0].location.file(), leaks[0].location.line()), (file!(), line));
+    }
+
+    #[test]
+    fn values_created_before_guard_or_by_other_threads_are_not_reported() {
+        let before = Verbose::new(-12);
+        let guard = LeakGuard::new();
+        thread::spawn(|| mem::forget(Verbose::new(-13))).join().unwrap();
+        mem::forget(before.clone());
+        guard.assert_leaks(&[-12]); // the clone
+    }
+
+    #[test]
+    fn a_value_which_is_not_registered_does_not_take_the_place_of_another() {
+        let guard = LeakGuard::new();
+        let registered = Verbose::new(-14);
+        drop(Verbose { id: -14, instance: 0 });
+        mem::forget(registered);
+        guard.assert_leaks(&[-14]);
+    }
+}
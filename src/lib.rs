@@ -1,40 +1,142 @@
 // only library can export modules that other crate can use (including binary crates in the same package)
 
+extern crate unicode_segmentation; // graphemes for src/pig_latin.rs
+extern crate memoize; // the crate of the '#[memoize]' attribute, memoize/src/lib.rs
+
+// the code is thrown away, but 'tests/compilation_errors.rs' compiles every snippet in place
+// and checks that it fails with the error written in the snippet's comment
 #[macro_export]
 macro_rules! compilation_error {
     ($e:stmt $(;)?) => {}
 }
 
+// the statement is only type checked, it runs when the package is built with '--features check-runtime-errors'
+// or when the macro is called from a test, then the statement must panic with the given message:
+//     runtime_error!("called `Option::unwrap()` on a `None` value", let y = absent_number.unwrap());
 #[macro_export]
 macro_rules! runtime_error {
-    ($s:stmt $(;)?) => {}
+    ($message:literal, $s:stmt $(;)?) => {
+        if $crate::checks::runtime_checks_enabled(cfg!(test)) {
+            #[allow(unused, arithmetic_overflow, unconditional_panic, clippy::out_of_bounds_indexing, clippy::unnecessary_literal_unwrap)]
+            let statement = || { $s; };
+            $crate::checks::expect_panic(file!(), line!(), Some($message), statement);
+        }
+    };
+    ($s:stmt $(;)?) => {
+        if $crate::checks::runtime_checks_enabled(cfg!(test)) {
+            #[allow(unused, arithmetic_overflow, unconditional_panic, clippy::out_of_bounds_indexing, clippy::unnecessary_literal_unwrap)]
+            let statement = || { $s; };
+            $crate::checks::expect_panic(file!(), line!(), None, statement);
+        }
+    };
 }
 
+// same as 'runtime_error!', but the statement panics only in debug build ('cfg!(debug_assertions)'),
+// a release build must not panic, and if an expected value is given it must be the result:
+//     debug_runtime_error!("attempt to add with overflow", release == -128, let x = i8::MAX + 1);
+// checked by '$ cargo test --features check-runtime-errors' and '$ cargo test --release --features check-runtime-errors'
 #[macro_export]
 macro_rules! debug_runtime_error {
-    ($s:stmt $(;)?) => {}
+    ($message:literal, release == $expected:expr, let $name:ident $(: $t:ty)? = $e:expr $(;)?) => {
+        $crate::debug_runtime_error!($message, release == $expected, { let $name $(: $t)? = $e; $name })
+    };
+    ($message:literal, release == $expected:expr, $e:expr $(;)?) => {
+        if $crate::checks::runtime_checks_enabled(cfg!(test)) {
+            #[allow(unused, arithmetic_overflow, unconditional_panic, clippy::out_of_bounds_indexing, clippy::unnecessary_literal_unwrap)]
+            let expression = || $e;
+            if cfg!(debug_assertions) {
+                $crate::checks::expect_panic(file!(), line!(), Some($message), move || { let _ = expression(); });
+            } else {
+                $crate::checks::expect_value(file!(), line!(), $expected, expression);
+            }
+        }
+    };
+    ($message:literal, $s:stmt $(;)?) => {
+        if $crate::checks::runtime_checks_enabled(cfg!(test)) {
+            #[allow(unused, arithmetic_overflow, unconditional_panic, clippy::out_of_bounds_indexing, clippy::unnecessary_literal_unwrap)]
+            let statement = || { $s; };
+            if cfg!(debug_assertions) {
+                $crate::checks::expect_panic(file!(), line!(), Some($message), statement);
+            } else {
+                $crate::checks::expect_no_panic(file!(), line!(), statement);
+            }
+        }
+    };
+    ($s:stmt $(;)?) => {
+        if $crate::checks::runtime_checks_enabled(cfg!(test)) {
+            #[allow(unused, arithmetic_overflow, unconditional_panic, clippy::out_of_bounds_indexing, clippy::unnecessary_literal_unwrap)]
+            let statement = || { $s; };
+            if cfg!(debug_assertions) {
+                $crate::checks::expect_panic(file!(), line!(), None, statement);
+            } else {
+                $crate::checks::expect_no_panic(file!(), line!(), statement);
+            }
+        }
+    };
 }
 
-#[derive(Debug)]
+pub mod catcher;
+pub mod checks;
+pub mod clock;
+pub mod directory;
+pub mod exercise;
+pub mod journal;
+pub mod leaks;
+pub mod limits;
+pub mod percent;
+pub mod pig_latin;
+pub mod stats;
+pub mod tracked;
+pub mod unique_digits;
+pub use tracked::Tracked;
+pub use memoize::memoize; // '#[memoize]' expands to code with '::myrust::catcher'
+
+use std::fmt;
+use std::panic::Location;
+use std::time::Duration;
+use journal::EventKind;
+
+// construction, cloning and dropping of every Verbose is recorded in 'journal', drops are printed
 pub struct Verbose {
-    pub id: i32
+    pub id: i32,
+    instance: u64, // in 'journal' and 'leaks', 0 - the value is not registered there
 }
 
 impl Verbose {
+    #[track_caller] // 'leaks' remembers where the value was created
     pub fn new(id: i32) -> Self {
-        Verbose { id }
+        let instance = journal::next_instance();
+        journal::record(id, instance, EventKind::Created);
+        leaks::register(id, instance, Location::caller());
+        Verbose { id, instance }
+    }
+}
+
+impl fmt::Debug for Verbose {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Verbose").field("id", &self.id).finish()
     }
 }
 
 impl Drop for Verbose {
     fn drop(&mut self) {
-        println!("Dropping {:?}", self);
+        if self.instance != 0 {
+            journal::record(self.id, self.instance, EventKind::Dropped);
+            leaks::unregister(self.instance);
+        }
+        if journal::printing() {
+            println!("Dropping {:?}", self);
+        }
     }
 }
 
 impl Clone for Verbose {
+    #[track_caller]
     fn clone(&self) -> Self {
-        Self { id: self.id }
+        let instance = journal::next_instance();
+        journal::record(self.id, instance, EventKind::Cloned { from: self.instance });
+        leaks::register(self.id, instance, Location::caller());
+        Verbose { id: self.id, instance }
     }
 }
 
@@ -61,33 +163,16 @@ impl Clone for Verbose {
 // '::' path separator
 
 // 'mod' defines a module, all members are private by default
-// front_of_house is implicitly child of module crate
+// front_of_house is implicitly child of module crate, 'pub' lets other crates (binaries) drive the restaurant
 #[allow(unused)]
-mod front_of_house {
+pub mod front_of_house {
     // hosting is a 'child' module of module front_of_house
     // hosting module is sibling to serving
-    pub mod hosting { // 'pub' makes names available from outside of this scope
-        pub fn add_to_waitlist() {}
-        pub fn seat_at_table() {}
-        fn prepare() { // private function, making module public does not affect on it's children
-            // relative path, relative to this module
-            add_to_waitlist();
-            // relative path, relative to this module
-            self::seat_at_table();
-            // path, relative to this module
-            self::super::look_at_the_personal();
-        }
-    }
+    // 'pub' makes names available from outside of this scope
+    pub mod hosting; // body of the module is in the file src/front_of_house/hosting.rs
     // serving is a 'child' module of module front_of_house
     // serving module is sibling to hosting
-    mod serving {
-        fn take_order() {
-            // path, relative to this module
-            super::look_at_the_personal()
-        }
-        fn serve_order() {}
-        fn take_payment() {}
-    }
+    pub mod serving; // body of the module is in the file src/front_of_house/serving.rs
 
     // privat for outsiders of front_of_house module, but accessible for children
     fn look_at_the_personal() {}
@@ -95,8 +180,12 @@ mod front_of_house {
 
 fn server_order() {}
 
-mod back_of_house {
-    #[derive(Debug)]
+pub mod back_of_house {
+    use std::time::Duration;
+    use clock::Clock;
+    use front_of_house::serving::Dish;
+
+    #[derive(Debug, Clone, PartialEq)]
     pub struct Breakfast {
         pub toasts: String,
         seasonal_fruit: String // this field is private for siblings
@@ -113,30 +202,51 @@ mod back_of_house {
     }
 
     // enum variants are public by default
-    #[allow(dead_code)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum Appetizer {
         Soup,
         Salad,
     }
-    #[allow(unused)]
-    fn fix_incorrect_order() {
+    pub mod kitchen; // body of the module is in the file src/back_of_house/kitchen.rs
+
+    // the order came back, it goes to the kitchen queue again
+    pub fn fix_incorrect_order(kitchen: &mut kitchen::Kitchen, completion: kitchen::Completion) {
         // this is not crate::front_of_house::serving::server_order
         super::server_order();
-        cook_order();
+        kitchen.requeue(completion);
+    }
+
+    // a cook spends preparation time of every dish on the clock, returns the time spent
+    pub fn cook_order(dishes: &[Dish], clock: &dyn Clock) -> Duration {
+        let mut spent = Duration::ZERO;
+        for dish in dishes {
+            let time = preparation_time(dish);
+            clock.sleep(time);
+            spent += time;
+        }
+        spent
     }
 
-    fn cook_order() {}
+    pub fn preparation_time(dish: &Dish) -> Duration {
+        let minutes = match *dish {
+            Dish::Breakfast(_) => 6,
+            Dish::Appetizer(Appetizer::Soup) => 10,
+            Dish::Appetizer(Appetizer::Salad) => 4,
+        };
+        Duration::from_secs(minutes * 60)
+    }
 }
 
 #[allow(path_statements)]
 pub fn eat_at_restaurant() {
+    let mut host = front_of_house::hosting::Host::new(&[2, 4]);
     // absolut path, that is because of use of 'crate::'
-    crate::front_of_house::hosting::add_to_waitlist();
+    crate::front_of_house::hosting::add_to_waitlist(&mut host, 3, Duration::ZERO).unwrap();
 
-    front_of_house::hosting::seat_at_table();
+    front_of_house::hosting::seat_at_table(&mut host, Duration::ZERO);
 
     compilation_error!(
-        front_of_house::hosting::prepare(); // function `prepare` is private
+        front_of_house::hosting::prepare(&mut host); // function `prepare` is private
     );
 
     let mut meal = back_of_house::Breakfast::summer("Rye");
@@ -157,8 +267,9 @@ fn eat_at_restaurant_2() {
     // introduces names in to the scope
     {
         use front_of_house::hosting::*;
-        add_to_waitlist();
-        seat_at_table();
+        let mut host = Host::new(&[2]);
+        add_to_waitlist(&mut host, 2, Duration::ZERO).unwrap();
+        seat_at_table(&mut host, Duration::ZERO);
     }
 }
 
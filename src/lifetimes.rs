@@ -49,18 +49,18 @@ fn main() {
         fn take_by_reference(v: &Verbose) {
             println!("Taken by reference {:?}", v)
         }
-        take_by_reference(&Verbose { id: 1 });
+        take_by_reference(&Verbose::new(1));
         println!("That is the problem, Verbose instance was dropped right after function call returns. We cannot return it reference from function to use.");
     }
     {
         fn take_by_reference_and_return(v: &Verbose) -> Verbose {
             println!("Taken by reference and returned dereferenced {:?}", v);
             compilation_error!(
-                *v // cannot move out of `*v` which is behind a shared reference
+                let moved = *v; // cannot move out of `*v` which is behind a shared reference
             );
             v.clone() // Ok,
         }
-        let v = take_by_reference_and_return(&Verbose { id: 2 });
+        let v = take_by_reference_and_return(&Verbose::new(2));
         // previous value was dropped here
         println!("Value moved from function {:?}", v);
     }
@@ -71,12 +71,12 @@ fn main() {
             v
         }
 
-        let v = take_by_reference_and_return_reference(&Verbose { id: 3 });
+        let v = take_by_reference_and_return_reference(&Verbose::new(3));
         compilation_error!(
             println!("Value returned by reference from function {:?}", v); // temporary value dropped while borrowed
         );
 
-        let v = Verbose { id: 3 };
+        let v = Verbose::new(3);
         let v = take_by_reference_and_return_reference(&v); // OK
         println!("Value returned by reference from function {:?}", v);
     }
@@ -99,23 +99,23 @@ fn main() {
         }
 
         println!("--> non of variables survives longer function call");
-        let v = take_by_reference_and_return_reference(&Verbose { id: 5 }, &Verbose { id: 6 });
+        let v = take_by_reference_and_return_reference(&Verbose::new(5), &Verbose::new(6));
         compilation_error!(
             println!("Returned value {:?}", v); // temporary value dropped while borrowed
         );
 
         println!("--> now one of variables live longer then before function returns");
-        let v = Verbose { id: 7 };
-        let vvv = take_by_reference_and_return_reference(&v, &Verbose { id: 8 });
+        let v = Verbose::new(7);
+        let vvv = take_by_reference_and_return_reference(&v, &Verbose::new(8));
         compilation_error!(
             println!("Returned value {:?}", vvv); // temporary value dropped while borrowed
         );
 
         // lifetime annotation are about end of lifetime of variables, no the whole lifetime
         println!("--> now both of variables live longer");
-        let v1 = Verbose { id: 9 };
+        let v1 = Verbose::new(9);
         {
-            let v2 = Verbose { id: 10 };
+            let v2 = Verbose::new(10);
             let vvv = take_by_reference_and_return_reference(&v1, &v2);
             println!("Returned value {:?}", vvv);
         }
@@ -123,7 +123,7 @@ fn main() {
     {
         compilation_error!(
             fn generate_verbose_object<'a>() -> &'a Verbose {
-                &Verbose { id: 11 } // cannot return reference to temporary value
+                &Verbose::new(11) // cannot return reference to temporary value
             }
         );
     }
@@ -135,15 +135,16 @@ fn main() {
 
         // lifetime annotation are about end of lifetime of variables, no the whole lifetime
         println!("--> now tow of variables live longer, but still not enough");
-        let v1 = Verbose { id: 9 };
+        let v1 = Verbose::new(9);
         let vvv;
         {
-            let v2 = Verbose { id: 10 };
+            let v2 = Verbose::new(10);
             vvv = take_by_reference_and_return_reference(&v1, &v2);
 
         }
+        // while v1 does, but it does not matter
         compilation_error!(
-            println!("Returned value {:?}", vvv); // `v2` does not live long enough. While v1 does, but it does not matter.
+            println!("Returned value {:?}", vvv); // `v2` does not live long enough
         );
     }
     {
@@ -182,14 +183,15 @@ fn main() {
         struct Something<'a> {
             text: &'a Verbose
         }
-        let smthn_late_init;
+        let v = Verbose::new(12);
+        let mut smthn_late_init = Something{ text: &v };
         {
-            let s = Verbose{ id: 11 };
+            let s = Verbose::new(11);
             compilation_error!(
                 smthn_late_init = Something{ text: &s }; // `s` does not live long enough
             );
         }
-        smthn_late_init = Verbose{ id: 12 }; // this line just to keep compiler happy
+        println!("The structure {:?}", smthn_late_init); // 'smthn_late_init' is used after 's' is gone
     }
     {
         // Special lifetime 'static, which means that this reference can live for the entire duration of the program.
@@ -0,0 +1,184 @@
+// the 'LimitTracker' of src/smart_pointers_boxes.rs with its own rules, it tells a 'Messenger' when a value
+// crosses a threshold on the way up:
+//     let mut tracker = LimitTracker::new(&messenger, 100)
+//         .with_thresholds(vec![Threshold::new(80, Severity::Warning, "80% used"), Threshold::new(100, Severity::Error, "no quota left")])
+//         .with_hysteresis(5);
+//     tracker.set_value(85); // "80% used"
+//     tracker.set_value(90); // nothing, 80% has been reported
+//     tracker.set_value(77); // nothing, not below 80 - 5 yet
+//     tracker.set_value(70); // "recovered", 80% can be reported again
+// When a value jumps over several thresholds at once only the highest one is sent.
+// A threshold is crossed again after the value falls below it minus the hysteresis, percentage points of max,
+// when it is so for all thresholds the recovered message is sent.
+// Messengers for the console, a log file, a channel, batches and routing by severity are in src/limits/messengers.rs,
+// quotas of many tenants with rolling windows and a token bucket are in src/limits/quota.rs.
+
+use std::fmt;
+
+mod messengers; // src/limits/messengers.rs
+pub use self::messengers::{Alert, Batching, ChannelMessenger, FanOut, Poller, WriteMessenger};
+mod quota; // src/limits/quota.rs
+pub use self::quota::{Decision, Quota, QuotaManager};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Urgent,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Urgent => "urgent",
+            Severity::Error => "error",
+        };
+        f.pad(name)
+    }
+}
+
+pub trait Messenger {
+    fn send(&self, severity: Severity, msg: &str);
+}
+
+impl<M: Messenger + ?Sized> Messenger for &M {
+    fn send(&self, severity: Severity, msg: &str) {
+        (**self).send(severity, msg)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Threshold {
+    pub percent: u32, // of max, 100 and more is over the limit
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Threshold {
+    pub fn new(percent: u32, severity: Severity, message: &str) -> Threshold {
+        Threshold { percent, severity, message: message.to_string() }
+    }
+}
+
+// the messenger is borrowed as in the book, '&messenger', or owned, an 'Arc' of it for example
+pub struct LimitTracker<T: Messenger> {
+    messenger: T,
+    value: usize,
+    max: usize,
+    thresholds: Vec<Threshold>, // ascending
+    armed: Vec<bool>, // the threshold is sent when it is crossed
+    hysteresis: u32,
+    recovered: String,
+}
+
+impl<T: Messenger> LimitTracker<T> {
+    // the thresholds of the book, 75%, 90% and 100%, without hysteresis
+    pub fn new(messenger: T, max: usize) -> LimitTracker<T> {
+        LimitTracker {
+            messenger,
+            value: 0,
+            max,
+            thresholds: vec![],
+            armed: vec![],
+            hysteresis: 0,
+            recovered: "Recovered: You are back under your quota".to_string(),
+        }
+        .with_thresholds(vec![
+            Threshold::new(75, Severity::Warning, "Warning: You've used up over 75% of your quota!"),
+            Threshold::new(90, Severity::Urgent, "Urgent warning: You've used up over 90% of your quota!"),
+            Threshold::new(100, Severity::Error, "Error: You are over your quota!"),
+        ])
+    }
+
+    // instead of the default ones, in any order
+    pub fn with_thresholds(mut self, mut thresholds: Vec<Threshold>) -> Self {
+        thresholds.sort_by_key(|t| t.percent);
+        self.armed = thresholds.iter().map(|t| !self.reached(t.percent)).collect();
+        self.thresholds = thresholds;
+        self
+    }
+
+    // percentage points of max
+    pub fn with_hysteresis(mut self, hysteresis: u32) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    // sent with Severity::Info
+    pub fn with_recovered_message(mut self, message: &str) -> Self {
+        self.recovered = message.to_string();
+        self
+    }
+
+    pub fn value(&self) -> usize {
+        self.value
+    }
+
+    pub fn set_value(&mut self, value: usize) {
+        self.value = value;
+        let alerting = self.armed.contains(&false);
+        let mut highest = None;
+        for (i, threshold) in self.thresholds.iter().enumerate() {
+            if self.armed[i] && self.reached(threshold.percent) {
+                self.armed[i] = false;
+                highest = Some(i);
+            } else if !self.armed[i] && !self.reached(threshold.percent.saturating_sub(self.hysteresis)) {
+                self.armed[i] = true;
+            }
+        }
+        if let Some(i) = highest {
+            self.messenger.send(self.thresholds[i].severity, &self.thresholds[i].message);
+        } else if alerting && !self.armed.contains(&false) {
+            self.messenger.send(Severity::Info, &self.recovered);
+        }
+    }
+
+    // the value is at least 'percent' of max, without floating point rounding
+    fn reached(&self, percent: u32) -> bool {
+        self.value as u128 * 100 >= percent as u128 * self.max as u128
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    struct Sent(RefCell<Vec<String>>);
+
+    impl Messenger for Sent {
+        fn send(&self, severity: Severity, msg: &str) {
+            self.0.borrow_mut().push(format!("{} {}", severity, msg));
+        }
+    }
+
+    #[test]
+    fn thresholds_are_sent_once_until_the_value_falls_below_the_hysteresis() {
+        let sent = Sent(RefCell::new(vec![]));
+        let mut tracker = LimitTracker::new(&sent, 200)
+            .with_thresholds(vec![Threshold::new(100, Severity::Error, "over"), Threshold::new(50, Severity::Warning, "half")])
+            .with_hysteresis(10)
+            .with_recovered_message("fine");
+        for &value in &[99, 100, 150, 90, 100, 79, 100, 210, 250, 190, 170, 200, 0, 0] {
+            tracker.set_value(value);
+        }
+        assert_eq!(*sent.0.borrow(), vec!["warning half", "info fine", "warning half", "error over", "error over", "info fine"]);
+    }
+
+    #[test]
+    fn the_highest_of_thresholds_crossed_at_once_is_sent() {
+        let sent = Sent(RefCell::new(vec![]));
+        let mut tracker = LimitTracker::new(&sent, 3);
+        tracker.set_value(3);
+        tracker.set_value(2); // 66%, below all of them
+        tracker.set_value(0);
+        assert_eq!(*sent.0.borrow(), vec!["error Error: You are over your quota!", "info Recovered: You are back under your quota"]);
+        // no limit at all, everything is over it
+        let mut zero = LimitTracker::new(&sent, 0);
+        zero.set_value(0);
+        assert_eq!(sent.0.borrow().len(), 2);
+    }
+}
@@ -0,0 +1,358 @@
+// body of 'limits::messengers', messengers to plug a 'LimitTracker' into:
+//     WriteMessenger::stderr()                   "warning: 80% used" lines, 'stdout()' or any 'io::Write'
+//     WriteMessenger::log_file("alerts.log")?    the same lines appended to a file
+//     ChannelMessenger::new(sender)              an 'Alert' for every message, for another thread to handle
+//     Batching::new(messenger, window, clock)    messages within 'window' after the first one go as one message
+//     Batching::spawn_poller(&batching, period)  with 'batching' in an 'Arc', sends a lone message after its window
+//     FanOut::new().with_route(Severity::Info, log).with_route(Severity::Urgent, pager)
+// A tracker never polls, without a poller 'Batching' keeps the last batch until the next message or a drop.
+// All of them can be shared between threads. Sending can not fail for a tracker, a message which can not be
+// written or sent is lost.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Stderr, Stdout, Write};
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use clock::Clock;
+use super::{Messenger, Severity};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alert {
+    pub severity: Severity,
+    pub message: String,
+}
+
+// a messenger shared by trackers
+impl<M: Messenger + ?Sized> Messenger for Arc<M> {
+    fn send(&self, severity: Severity, msg: &str) {
+        (**self).send(severity, msg)
+    }
+}
+
+impl<M: Messenger + ?Sized> Messenger for Box<M> {
+    fn send(&self, severity: Severity, msg: &str) {
+        (**self).send(severity, msg)
+    }
+}
+
+// a line "<severity>: <message>" for every message
+pub struct WriteMessenger<W: Write> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write> WriteMessenger<W> {
+    pub fn new(writer: W) -> WriteMessenger<W> {
+        WriteMessenger { writer: Mutex::new(writer) }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl WriteMessenger<Stdout> {
+    pub fn stdout() -> WriteMessenger<Stdout> {
+        WriteMessenger::new(io::stdout())
+    }
+}
+
+impl WriteMessenger<Stderr> {
+    pub fn stderr() -> WriteMessenger<Stderr> {
+        WriteMessenger::new(io::stderr())
+    }
+}
+
+impl WriteMessenger<File> {
+    // lines are appended, the file is made if there is none
+    pub fn log_file<P: AsRef<Path>>(path: P) -> io::Result<WriteMessenger<File>> {
+        Ok(WriteMessenger::new(OpenOptions::new().create(true).append(true).open(path)?))
+    }
+}
+
+impl<W: Write> Messenger for WriteMessenger<W> {
+    fn send(&self, severity: Severity, msg: &str) {
+        let mut writer = self.writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // one write for the whole line, so in a file opened for appending lines of different threads do not mix,
+        // stdout and stderr give no such promise
+        let line = format!("{}: {}\n", severity, msg);
+        let _ = writer.write_all(line.as_bytes()).and_then(|()| writer.flush());
+    }
+}
+
+pub struct ChannelMessenger {
+    sender: Mutex<Sender<Alert>>,
+}
+
+impl ChannelMessenger {
+    pub fn new(sender: Sender<Alert>) -> ChannelMessenger {
+        ChannelMessenger { sender: Mutex::new(sender) }
+    }
+}
+
+impl Messenger for ChannelMessenger {
+    // nothing happens when the receiver is gone
+    fn send(&self, severity: Severity, msg: &str) {
+        let sender = self.sender.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = sender.send(Alert { severity, message: msg.to_string() });
+    }
+}
+
+// Messages are kept from the first one for 'window', then they are sent as one, with the highest severity
+// of them, texts joined by "; " and repeated texts counted: "disk full (x3); cpu hot".
+// Nothing runs in the background, the batch goes when a message comes after the window or when 'poll'
+// finds the window has passed, a loop, a timer or 'spawn_poller' calls it. 'flush' sends kept messages at once, dropping flushes too.
+pub struct Batching<M: Messenger> {
+    messenger: M,
+    window: Duration,
+    clock: Arc<dyn Clock>,
+    batch: Mutex<Batch>,
+}
+
+#[derive(Default)]
+struct Batch {
+    started: Duration,
+    alerts: Vec<(Alert, usize)>, // in the order of the first one, how many times
+}
+
+impl Batch {
+    fn take_if_over(&mut self, now: Duration, window: Duration) -> Vec<(Alert, usize)> {
+        if !self.alerts.is_empty() && now >= self.started + window {
+            std::mem::take(&mut self.alerts)
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl<M: Messenger> Batching<M> {
+    pub fn new(messenger: M, window: Duration, clock: Arc<dyn Clock>) -> Batching<M> {
+        Batching { messenger, window, clock, batch: Mutex::new(Batch::default()) }
+    }
+
+    pub fn flush(&self) {
+        let alerts = std::mem::take(&mut self.lock().alerts);
+        self.deliver(alerts);
+    }
+
+    // sends the batch if its window has passed
+    pub fn poll(&self) {
+        let now = self.clock.now();
+        let alerts = self.lock().take_if_over(now, self.window);
+        self.deliver(alerts);
+    }
+
+    // a thread which polls every 'period', it holds only a weak reference to the batching
+    pub fn spawn_poller(batching: &Arc<Self>, period: Duration) -> Poller
+        where M: Send + Sync + 'static
+    {
+        let stop = Arc::new(Stop::default());
+        let (weak, stopped) = (Arc::downgrade(batching), stop.clone());
+        let thread = thread::spawn(move || {
+            while !stopped.wait(period) {
+                match weak.upgrade() {
+                    Some(batching) => batching.poll(),
+                    None => return,
+                }
+            }
+        });
+        Poller { stop, thread: Some(thread) }
+    }
+
+    // not under the lock, the messenger may be slow
+    fn deliver(&self, alerts: Vec<(Alert, usize)>) {
+        if alerts.is_empty() {
+            return;
+        }
+        let severity = alerts.iter().map(|(alert, _)| alert.severity).max().unwrap_or(Severity::Info);
+        let texts: Vec<String> = alerts.into_iter()
+            .map(|(alert, times)| match times {
+                1 => alert.message,
+                _ => format!("{} (x{})", alert.message, times),
+            })
+            .collect();
+        self.messenger.send(severity, &texts.join("; "));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Batch> {
+        self.batch.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<M: Messenger> Messenger for Batching<M> {
+    fn send(&self, severity: Severity, msg: &str) {
+        let now = self.clock.now();
+        let over = {
+            let mut batch = self.lock();
+            let over = batch.take_if_over(now, self.window);
+            if batch.alerts.is_empty() {
+                batch.started = now;
+            }
+            match batch.alerts.iter_mut().find(|(alert, _)| alert.severity == severity && alert.message == msg) {
+                Some(counted) => counted.1 += 1,
+                None => batch.alerts.push((Alert { severity, message: msg.to_string() }, 1)),
+            }
+            over
+        };
+        self.deliver(over);
+    }
+}
+
+impl<M: Messenger> Drop for Batching<M> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[derive(Default)]
+struct Stop {
+    stopped: Mutex<bool>,
+    signal: Condvar,
+}
+
+impl Stop {
+    // true - stopped, not just the time has passed
+    fn wait(&self, timeout: Duration) -> bool {
+        let stopped = self.stopped.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let (stopped, _) = self.signal.wait_timeout_while(stopped, timeout, |stopped| !*stopped)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *stopped
+    }
+}
+
+// stops the thread of 'spawn_poller' when dropped
+pub struct Poller {
+    stop: Arc<Stop>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        *self.stop.stopped.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = true;
+        self.stop.signal.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// every messenger gets messages of its severity or higher
+#[derive(Default)]
+pub struct FanOut {
+    routes: Vec<(Severity, Box<dyn Messenger + Send + Sync>)>,
+}
+
+impl FanOut {
+    pub fn new() -> FanOut {
+        FanOut { routes: vec![] }
+    }
+
+    pub fn with_route<M: Messenger + Send + Sync + 'static>(mut self, min: Severity, messenger: M) -> Self {
+        self.routes.push((min, Box::new(messenger)));
+        self
+    }
+}
+
+impl Messenger for FanOut {
+    fn send(&self, severity: Severity, msg: &str) {
+        for (min, messenger) in &self.routes {
+            if severity >= *min {
+                messenger.send(severity, msg);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::LimitTracker;
+    use clock::ManualClock;
+    use std::fs;
+    use std::sync::mpsc;
+
+    #[test]
+    fn lines_are_written_and_appended() {
+        let lines = WriteMessenger::new(vec![]);
+        lines.send(Severity::Warning, "80% used");
+        lines.send(Severity::Error, "over");
+        assert_eq!(String::from_utf8(lines.into_inner()).unwrap(), "warning: 80% used\nerror: over\n");
+
+        let path = std::env::temp_dir().join(format!("myrust_alerts_{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        WriteMessenger::log_file(&path).unwrap().send(Severity::Info, "first");
+        WriteMessenger::log_file(&path).unwrap().send(Severity::Urgent, "second");
+        let log = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(log, "info: first\nurgent: second\n");
+    }
+
+    #[test]
+    fn messages_within_a_window_go_together() {
+        let (sender, receiver) = mpsc::channel();
+        let clock = Arc::new(ManualClock::new());
+        let batching = Batching::new(ChannelMessenger::new(sender), Duration::from_secs(10), clock.clone());
+        batching.send(Severity::Warning, "disk");
+        clock.advance(Duration::from_secs(3));
+        batching.send(Severity::Urgent, "cpu");
+        batching.send(Severity::Warning, "disk");
+        assert!(receiver.try_recv().is_err());
+        clock.advance(Duration::from_secs(7));
+        batching.send(Severity::Info, "later"); // starts the next window
+        assert_eq!(receiver.try_recv(), Ok(Alert { severity: Severity::Urgent, message: "disk (x2); cpu".to_string() }));
+        drop(batching);
+        assert_eq!(receiver.try_recv(), Ok(Alert { severity: Severity::Info, message: "later".to_string() }));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn poll_sends_a_lone_message_after_the_window() {
+        let (sender, receiver) = mpsc::channel();
+        let clock = Arc::new(ManualClock::new());
+        let batching = Batching::new(ChannelMessenger::new(sender), Duration::from_secs(10), clock.clone());
+        batching.send(Severity::Error, "over");
+        clock.advance(Duration::from_secs(9));
+        batching.poll();
+        assert!(receiver.try_recv().is_err());
+        clock.advance(Duration::from_secs(1));
+        batching.poll();
+        assert_eq!(receiver.try_recv(), Ok(Alert { severity: Severity::Error, message: "over".to_string() }));
+        batching.poll();
+        drop(batching);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn poller_sends_a_lone_message_of_a_tracker() {
+        let (sender, receiver) = mpsc::channel();
+        let clock = Arc::new(ManualClock::new());
+        let batching = Arc::new(Batching::new(ChannelMessenger::new(sender), Duration::from_secs(10), clock.clone()));
+        let poller = Batching::spawn_poller(&batching, Duration::from_millis(5));
+        LimitTracker::new(&batching, 100).set_value(80);
+        clock.advance(Duration::from_secs(10));
+        let alert = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(alert.severity, Severity::Warning);
+        drop(poller);
+        drop(batching);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn fan_out_routes_by_severity() {
+        let (log, logged) = mpsc::channel();
+        let (pager, paged) = mpsc::channel();
+        let alerts = FanOut::new()
+            .with_route(Severity::Info, ChannelMessenger::new(log))
+            .with_route(Severity::Urgent, ChannelMessenger::new(pager));
+        let mut tracker = LimitTracker::new(&alerts, 100);
+        for value in [80, 95, 100, 10] {
+            tracker.set_value(value);
+        }
+        drop(alerts);
+        let severities = |alerts: mpsc::Receiver<Alert>| alerts.iter().map(|a| a.severity).collect::<Vec<_>>();
+        assert_eq!(severities(logged), vec![Severity::Warning, Severity::Urgent, Severity::Error, Severity::Info]);
+        assert_eq!(severities(paged), vec![Severity::Urgent, Severity::Error]);
+    }
+}
@@ -0,0 +1,388 @@
+// body of 'limits::quota', quotas of many tenants:
+//     let mut quotas = QuotaManager::new(Quota::new().with_per_minute(100).with_per_day(10_000), messenger)
+//         .with_tenant("acme", Quota::new().with_per_minute(1000).with_token_bucket(50, Duration::from_millis(100)))
+//         .with_clock(clock);
+//     match quotas.try_consume("acme", 3) {
+//         Decision::Allowed => serve(),
+//         Decision::Denied(retry_after) => reject(retry_after),
+//     }
+// A tenant without a quota of its own has the default one. Windows are rolling, in 60 steps: a minute moves
+// by seconds, an hour by minutes and a day by 24 minutes, a consumption is counted in its step and leaves
+// the window 60 steps later.
+// The token bucket holds 'capacity' tokens and gets one back every 'interval', it allows bursts of 'capacity'.
+// A denied consumption takes nothing, 'retry_after' is the time until it fits in all windows and the bucket,
+// Duration::MAX if it never fits as it is more than a limit.
+// Every window of every tenant has a 'LimitTracker', the warnings at 75%, 90% and 100% of it go to the messenger.
+// Quotas of tenants can be set and removed at any time. A tenant which consumed nothing for a day and has its bucket
+// full is forgotten, the number of tenants only grows until the next sweep, which runs when it doubles.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use clock::{Clock, SystemClock};
+use super::{LimitTracker, Messenger, Severity, Threshold};
+
+const STEPS: u64 = 60; // of a window
+const FIRST_SWEEP: usize = 64; // tenants
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Allowed,
+    Denied(Duration), // retry after
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Quota {
+    pub per_minute: Option<u64>,
+    pub per_hour: Option<u64>,
+    pub per_day: Option<u64>,
+    pub token_bucket: Option<(u32, Duration)>, // capacity, a token comes back every interval
+}
+
+impl Quota {
+    // no limits
+    pub fn new() -> Quota {
+        Quota::default()
+    }
+
+    pub fn with_per_minute(mut self, limit: u64) -> Self {
+        self.per_minute = Some(limit);
+        self
+    }
+
+    pub fn with_per_hour(mut self, limit: u64) -> Self {
+        self.per_hour = Some(limit);
+        self
+    }
+
+    pub fn with_per_day(mut self, limit: u64) -> Self {
+        self.per_day = Some(limit);
+        self
+    }
+
+    pub fn with_token_bucket(mut self, capacity: u32, interval: Duration) -> Self {
+        self.token_bucket = Some((capacity, interval));
+        self
+    }
+}
+
+pub struct QuotaManager<M: Messenger> {
+    default: Quota,
+    quotas: HashMap<String, Quota>,
+    tenants: HashMap<String, Tenant<M>>,
+    sweep_at: usize, // idle tenants are forgotten when there are so many of them
+    messenger: Arc<M>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<M: Messenger> QuotaManager<M> {
+    pub fn new(default: Quota, messenger: M) -> QuotaManager<M> {
+        QuotaManager {
+            default,
+            quotas: HashMap::new(),
+            tenants: HashMap::new(),
+            sweep_at: FIRST_SWEEP,
+            messenger: Arc::new(messenger),
+            clock: Arc::new(SystemClock::new()),
+        }
+    }
+
+    // consumption so far of the tenant is forgotten
+    pub fn with_tenant(mut self, tenant: &str, quota: Quota) -> Self {
+        self.set_tenant(tenant, quota);
+        self
+    }
+
+    // the same for a manager in use
+    pub fn set_tenant(&mut self, tenant: &str, quota: Quota) {
+        self.quotas.insert(tenant.to_string(), quota);
+        self.tenants.remove(tenant);
+    }
+
+    // the tenant has the default quota again, its consumption is forgotten, returns the quota it had
+    pub fn remove_tenant(&mut self, tenant: &str) -> Option<Quota> {
+        self.tenants.remove(tenant);
+        self.quotas.remove(tenant)
+    }
+
+    // tenants with consumption which is not forgotten yet
+    pub fn active_tenants(&self) -> usize {
+        self.tenants.len()
+    }
+
+    // SystemClock by default
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self.tenants.clear();
+        self
+    }
+
+    pub fn try_consume(&mut self, tenant: &str, amount: u64) -> Decision {
+        let now = self.clock.now();
+        if !self.tenants.contains_key(tenant) {
+            if self.tenants.len() >= self.sweep_at {
+                self.forget_idle(now);
+            }
+            let quota = self.quotas.get(tenant).copied().unwrap_or(self.default);
+            self.tenants.insert(tenant.to_string(), Tenant::new(tenant, &quota, &self.messenger, now));
+        }
+        let state = self.tenants.get_mut(tenant).expect("the tenant is added above");
+        for window in &mut state.windows {
+            window.roll(now);
+        }
+        let wait = state.windows.iter().map(|w| w.retry_after(now, amount))
+            .chain(state.bucket.as_ref().map(|b| b.retry_after(now, amount)))
+            .max()
+            .unwrap_or(Duration::ZERO);
+        let decision = if wait == Duration::ZERO {
+            for window in &mut state.windows {
+                window.add(now, amount);
+            }
+            if let Some(ref mut bucket) = state.bucket {
+                bucket.take(now, amount);
+            }
+            Decision::Allowed
+        } else {
+            Decision::Denied(wait)
+        };
+        for window in &mut state.windows {
+            window.tracker.set_value(window.used.min(usize::MAX as u64) as usize);
+        }
+        decision
+    }
+
+    // consumed in the window of that length at the last 'try_consume', None for a tenant without it
+    pub fn used(&self, tenant: &str, window: Duration) -> Option<u64> {
+        self.tenants.get(tenant)?.windows.iter().find(|w| w.length == window).map(|w| w.used)
+    }
+
+    // a forgotten tenant starts as a new one, which is the same as an idle one
+    fn forget_idle(&mut self, now: Duration) {
+        self.tenants.retain(|_, tenant| !tenant.is_idle(now));
+        self.sweep_at = FIRST_SWEEP.max(self.tenants.len() * 2);
+    }
+}
+
+struct Tenant<M: Messenger> {
+    windows: Vec<Window<M>>,
+    bucket: Option<Bucket>,
+}
+
+impl<M: Messenger> Tenant<M> {
+    fn new(name: &str, quota: &Quota, messenger: &Arc<M>, now: Duration) -> Tenant<M> {
+        let windows = [(quota.per_minute, 60, "per-minute"), (quota.per_hour, 60 * 60, "per-hour"), (quota.per_day, 24 * 60 * 60, "per-day")]
+            .iter()
+            .filter_map(|&(limit, seconds, window)| {
+                let tracker = LimitTracker::new(messenger.clone(), limit?.min(usize::MAX as u64) as usize)
+                    .with_thresholds(vec![
+                        Threshold::new(75, Severity::Warning, &format!("Warning: '{}' has used up over 75% of its {} quota!", name, window)),
+                        Threshold::new(90, Severity::Urgent, &format!("Urgent warning: '{}' has used up over 90% of its {} quota!", name, window)),
+                        Threshold::new(100, Severity::Error, &format!("Error: '{}' has used up all of its {} quota!", name, window)),
+                    ])
+                    .with_recovered_message(&format!("Recovered: '{}' is back under its {} quota", name, window));
+                Some(Window::new(Duration::from_secs(seconds), limit?, tracker))
+            })
+            .collect();
+        let bucket = quota.token_bucket.map(|(capacity, interval)| Bucket { capacity, interval, full_at: now });
+        Tenant { windows, bucket }
+    }
+
+    // all windows are empty and the bucket is full, the trackers send their recovered messages
+    fn is_idle(&mut self, now: Duration) -> bool {
+        for window in &mut self.windows {
+            window.roll(now);
+        }
+        let idle = self.windows.iter().all(|w| w.used == 0) && self.bucket.as_ref().is_none_or(|b| b.full_at <= now);
+        if idle {
+            for window in &mut self.windows {
+                window.tracker.set_value(0);
+            }
+        }
+        idle
+    }
+}
+
+struct Window<M: Messenger> {
+    length: Duration,
+    limit: u64,
+    steps: VecDeque<(u64, u64)>, // the number of a step since the clock started, consumed in it
+    used: u64, // in all steps
+    tracker: LimitTracker<Arc<M>>,
+}
+
+impl<M: Messenger> Window<M> {
+    fn new(length: Duration, limit: u64, tracker: LimitTracker<Arc<M>>) -> Window<M> {
+        Window { length, limit, steps: VecDeque::new(), used: 0, tracker }
+    }
+
+    fn step_length(&self) -> Duration {
+        self.length / STEPS as u32
+    }
+
+    fn step(&self, now: Duration) -> u64 {
+        (now.as_nanos() / self.step_length().as_nanos()) as u64
+    }
+
+    // forgets steps which are out of the window
+    fn roll(&mut self, now: Duration) {
+        let current = self.step(now);
+        while let Some(&(step, amount)) = self.steps.front() {
+            if step + STEPS > current {
+                break;
+            }
+            self.used -= amount;
+            self.steps.pop_front();
+        }
+    }
+
+    fn retry_after(&self, now: Duration, amount: u64) -> Duration {
+        if amount > self.limit {
+            return Duration::MAX;
+        }
+        let mut used = self.used;
+        if used + amount <= self.limit {
+            return Duration::ZERO;
+        }
+        for &(step, consumed) in &self.steps {
+            used -= consumed;
+            if used + amount <= self.limit {
+                // the window has moved past the step then
+                return self.step_length() * (step + STEPS) as u32 - now;
+            }
+        }
+        unreachable!("the window fits 'amount' when it is empty")
+    }
+
+    fn add(&mut self, now: Duration, amount: u64) {
+        let current = self.step(now);
+        match self.steps.back_mut() {
+            Some(&mut (step, ref mut consumed)) if step == current => *consumed += amount,
+            _ => self.steps.push_back((current, amount)),
+        }
+        self.used += amount;
+    }
+}
+
+// kept as the time the bucket is full again, which needs no refilling
+struct Bucket {
+    capacity: u32,
+    interval: Duration,
+    full_at: Duration,
+}
+
+impl Bucket {
+    fn retry_after(&self, now: Duration, amount: u64) -> Duration {
+        if amount > self.capacity as u64 {
+            return Duration::MAX;
+        }
+        // the tokens taken now would come back at 'full_at', the bucket can lend 'capacity' of them
+        let full_at = self.full_at.max(now) + self.interval * amount as u32;
+        (full_at - now).saturating_sub(self.interval * self.capacity)
+    }
+
+    fn take(&mut self, now: Duration, amount: u64) {
+        self.full_at = self.full_at.max(now) + self.interval * amount as u32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock::ManualClock;
+    use limits::{Alert, ChannelMessenger};
+    use std::sync::mpsc;
+
+    fn manager(default: Quota) -> (QuotaManager<ChannelMessenger>, Arc<ManualClock>, mpsc::Receiver<Alert>) {
+        let (sender, alerts) = mpsc::channel();
+        let clock = Arc::new(ManualClock::new());
+        let quotas = QuotaManager::new(default, ChannelMessenger::new(sender)).with_clock(clock.clone());
+        (quotas, clock, alerts)
+    }
+
+    #[test]
+    fn windows_roll() {
+        let (mut quotas, clock, _) = manager(Quota::new().with_per_minute(10).with_per_hour(25));
+        assert_eq!(quotas.try_consume("acme", 6), Decision::Allowed);
+        clock.advance(Duration::from_millis(30_500));
+        assert_eq!(quotas.try_consume("acme", 4), Decision::Allowed);
+        // 6 leave the minute at 60 s, 4 at 90 s
+        assert_eq!(quotas.try_consume("acme", 1), Decision::Denied(Duration::from_millis(29_500)));
+        assert_eq!(quotas.try_consume("acme", 7), Decision::Denied(Duration::from_millis(59_500)));
+        assert_eq!(quotas.try_consume("acme", 11), Decision::Denied(Duration::MAX));
+        assert_eq!(quotas.try_consume("other", 10), Decision::Allowed); // tenants are apart
+        clock.set(Duration::from_secs(60));
+        assert_eq!(quotas.try_consume("acme", 6), Decision::Allowed);
+        assert_eq!(quotas.used("acme", Duration::from_secs(60)), Some(10));
+        assert_eq!(quotas.used("acme", Duration::from_secs(3600)), Some(16));
+        // the hour is the limit now, 9 more until the first 6 leave it
+        clock.set(Duration::from_secs(200));
+        assert_eq!(quotas.try_consume("acme", 9), Decision::Allowed);
+        assert_eq!(quotas.try_consume("acme", 1), Decision::Denied(Duration::from_secs(3600 - 200)));
+        assert_eq!(quotas.used("acme", Duration::from_secs(86_400)), None);
+    }
+
+    #[test]
+    fn token_bucket_allows_bursts_and_refills() {
+        let (quotas, clock, _) = manager(Quota::new());
+        let mut quotas = quotas.with_tenant("api", Quota::new().with_token_bucket(5, Duration::from_secs(2)));
+        assert_eq!(quotas.try_consume("api", 5), Decision::Allowed);
+        assert_eq!(quotas.try_consume("api", 2), Decision::Denied(Duration::from_secs(4)));
+        clock.advance(Duration::from_secs(3)); // one token back, a half of another one
+        assert_eq!(quotas.try_consume("api", 1), Decision::Allowed);
+        assert_eq!(quotas.try_consume("api", 1), Decision::Denied(Duration::from_secs(1)));
+        clock.advance(Duration::from_secs(100));
+        assert_eq!(quotas.try_consume("api", 5), Decision::Allowed);
+        assert_eq!(quotas.try_consume("api", 6), Decision::Denied(Duration::MAX));
+        assert_eq!(quotas.try_consume("anyone", 1_000_000), Decision::Allowed); // the default has no limits
+    }
+
+    #[test]
+    fn tenants_are_set_and_removed_in_use() {
+        let (mut quotas, _, _) = manager(Quota::new().with_per_minute(10));
+        assert_eq!(quotas.try_consume("acme", 10), Decision::Allowed);
+        quotas.set_tenant("acme", Quota::new().with_per_minute(100));
+        assert_eq!(quotas.try_consume("acme", 100), Decision::Allowed); // the old consumption is forgotten
+        assert_eq!(quotas.remove_tenant("acme"), Some(Quota::new().with_per_minute(100)));
+        assert_eq!(quotas.remove_tenant("acme"), None);
+        assert_eq!(quotas.try_consume("acme", 11), Decision::Denied(Duration::MAX)); // the default again
+    }
+
+    #[test]
+    fn idle_tenants_are_forgotten() {
+        let (mut quotas, clock, _) = manager(Quota::new().with_per_day(1000).with_token_bucket(10, Duration::from_secs(1)));
+        for i in 0..FIRST_SWEEP {
+            quotas.try_consume(&format!("tenant{}", i), 1);
+        }
+        assert_eq!(quotas.active_tenants(), FIRST_SWEEP);
+        clock.advance(Duration::from_secs(86_400 - 60));
+        quotas.try_consume("busy", 1); // the others consumed within a day
+        assert_eq!(quotas.active_tenants(), FIRST_SWEEP + 1);
+        clock.advance(Duration::from_secs(60));
+        for i in 0..FIRST_SWEEP {
+            quotas.try_consume(&format!("new{}", i), 1);
+        }
+        // the sweep at 'new{FIRST_SWEEP - 1}' has forgotten the first tenants, but not 'busy'
+        assert_eq!(quotas.active_tenants(), FIRST_SWEEP + 1);
+        assert_eq!(quotas.used("busy", Duration::from_secs(86_400)), Some(1));
+        assert_eq!(quotas.used("tenant0", Duration::from_secs(86_400)), None);
+    }
+
+    #[test]
+    fn warnings_go_to_the_messenger() {
+        let (mut quotas, clock, alerts) = manager(Quota::new().with_per_minute(100));
+        for _ in 0..10 {
+            quotas.try_consume("acme", 10);
+        }
+        quotas.try_consume("acme", 10); // denied, nothing new
+        clock.advance(Duration::from_secs(60));
+        quotas.try_consume("acme", 1);
+        drop(quotas);
+        let sent: Vec<(Severity, String)> = alerts.iter().map(|a| (a.severity, a.message)).collect();
+        assert_eq!(sent, vec![
+            (Severity::Warning, "Warning: 'acme' has used up over 75% of its per-minute quota!".to_string()),
+            (Severity::Urgent, "Urgent warning: 'acme' has used up over 90% of its per-minute quota!".to_string()),
+            (Severity::Error, "Error: 'acme' has used up all of its per-minute quota!".to_string()),
+            (Severity::Info, "Recovered: 'acme' is back under its per-minute quota".to_string()),
+        ]);
+    }
+}
@@ -102,7 +102,7 @@ fn main() {
         fn does_not_take_ownership(s: &Verbose) {
             println!("function 'does_not_take_ownership' can access fields of referenced value Verbose.x is {:?}", s);
             compilation_error!(
-                *s = Verbose { x: i32::MAX }; // `s` is a `&` immutable reference, so the data it refers to cannot be written
+                *s = Verbose::new(i32::MAX); // `s` is a `&` reference, so the data it refers to cannot be written
             );
             // no drop called for s, because it does not have ownership
         }
@@ -134,7 +134,7 @@ fn main() {
 
         // due to two mutable references was defined in the same scope
         compilation_error!(
-            let s1 = *r2; // cannot borrow `s` as mutable more than once at a time
+            let s1 = r1.len(); // cannot borrow `s` as mutable more than once at a time
         );
         compilation_error!(
             let mut s1 = *r2; // cannot move out of `*r2` which is behind a mutable reference
@@ -224,8 +224,9 @@ fn main() {
         // A string slice is a reference to part of a String
         s = String::from("a new string");
         let a_slice = &s[1..7]; // end_index is not inclusive, always immutable borrow, a_slice: &str
+        // a slice can not be moved out of the string
         compilation_error!(
-            let a_slice = s[1..7]; // cannot move
+            let a_slice = s[1..7]; // the size for values of type `str` cannot be known at compilation time
         );
         println!("The slice of the string '{}' is '{}'", s, a_slice);
 
@@ -0,0 +1,346 @@
+// percent-encoding of RFC 3986, text is encoded as UTF-8 bytes:
+//     encode("a b/ü", EncodeSet::PATH)  == "a%20b/%C3%BC"
+//     encode("a b&c", EncodeSet::FORM)  == "a+b%26c"
+//     decode("a%20b/%C3%BC")            == Ok("a b/ü")
+//     decode("a%2")                     == Err(InvalidEscape { position: 1 })
+// Letters and digits are never encoded, a set tells which other ASCII bytes stay as they are.
+// Positions in errors are byte offsets in the input.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodeSet {
+    keep: u128, // a bit for every ASCII byte which is not encoded
+    plus_for_space: bool,
+}
+
+impl EncodeSet {
+    // unreserved characters only, for a single path segment or a query value
+    pub const COMPONENT: EncodeSet = EncodeSet::keeping(b"-._~");
+    // segments and '/' between them
+    pub const PATH: EncodeSet = EncodeSet::keeping(b"-._~!$&'()*+,;=:@/");
+    // a name or a value of a query pair, '&', '=' and '+' are encoded because they split a query into pairs
+    pub const QUERY_COMPONENT: EncodeSet = EncodeSet::keeping(b"-._~!$'()*,;:@/?");
+    // application/x-www-form-urlencoded, space is '+'
+    pub const FORM: EncodeSet = EncodeSet::keeping(b"-._*").with_plus_for_space();
+
+    // letters, digits and 'extra', non-ASCII bytes in 'extra' are ignored
+    pub const fn keeping(extra: &[u8]) -> EncodeSet {
+        let mut keep = 0u128;
+        let mut c = 0;
+        while c < 128 {
+            if (c as u8).is_ascii_alphanumeric() {
+                keep |= 1 << c;
+            }
+            c += 1;
+        }
+        let mut i = 0;
+        while i < extra.len() {
+            if extra[i] < 128 {
+                keep |= 1 << extra[i];
+            }
+            i += 1;
+        }
+        EncodeSet { keep, plus_for_space: false }
+    }
+
+    pub const fn with_plus_for_space(mut self) -> EncodeSet {
+        self.plus_for_space = true;
+        self
+    }
+
+    pub fn encodes(&self, byte: u8) -> bool {
+        byte >= 128 || self.keep & (1 << byte) == 0
+    }
+
+    // appends the encoded 'byte' to 'out'
+    fn encode_byte(&self, byte: u8, out: &mut Vec<u8>) {
+        if byte == b' ' && self.plus_for_space {
+            out.push(b'+');
+        } else if self.encodes(byte) {
+            out.extend_from_slice(&[b'%', HEX[(byte >> 4) as usize], HEX[(byte & 0xF) as usize]]);
+        } else {
+            out.push(byte);
+        }
+    }
+
+    fn encoded_len(&self, byte: u8) -> usize {
+        if self.encodes(byte) && !(byte == b' ' && self.plus_for_space) {
+            3
+        } else {
+            1
+        }
+    }
+}
+
+const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PercentError {
+    InvalidEscape { position: usize }, // '%' without two hex digits after it
+    InvalidUtf8 { position: usize },   // where the decoded bytes stop being UTF-8
+    NoRoom { needed: usize, available: usize },
+}
+
+impl fmt::Display for PercentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PercentError::InvalidEscape { position } => write!(f, "invalid escape at {}", position),
+            PercentError::InvalidUtf8 { position } => write!(f, "decoded text is not UTF-8 at {}", position),
+            PercentError::NoRoom { needed, available } => write!(f, "needs {} places for the encoded text, has {}", needed, available),
+        }
+    }
+}
+
+impl std::error::Error for PercentError {}
+
+pub fn encode(text: &str, set: EncodeSet) -> String {
+    encode_bytes(text.as_bytes(), set)
+}
+
+pub fn encode_bytes(bytes: &[u8], set: EncodeSet) -> String {
+    let mut out = Vec::with_capacity(bytes.len());
+    for &b in bytes {
+        set.encode_byte(b, &mut out);
+    }
+    String::from_utf8(out).expect("encoded text is ASCII")
+}
+
+pub fn decode(text: &str) -> Result<String, PercentError> {
+    to_utf8(text, decode_bytes(text, false)?)
+}
+
+// '+' is a space
+pub fn decode_form(text: &str) -> Result<String, PercentError> {
+    to_utf8(text, decode_bytes(text, true)?)
+}
+
+pub fn decode_bytes(text: &str, plus_is_space: bool) -> Result<Vec<u8>, PercentError> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let high = bytes.get(i + 1).and_then(|&b| hex_value(b));
+                let low = bytes.get(i + 2).and_then(|&b| hex_value(b));
+                match (high, low) {
+                    (Some(high), Some(low)) => out.push(high << 4 | low),
+                    _ => return Err(PercentError::InvalidEscape { position: i }),
+                }
+                i += 3;
+            }
+            b'+' if plus_is_space => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    Ok(out)
+}
+
+// encodes the first 'length' chars of 'buf' in place, returns the new length.
+// Chars after 'length' are garbage and are overwritten, there must be enough of them:
+//     let mut buf: Vec<char> = "a b  ".chars().collect();
+//     encode_chars_in_place(&mut buf, 3, EncodeSet::PATH) == Ok(5), buf == "a%20b"
+pub fn encode_chars_in_place(buf: &mut [char], length: usize, set: EncodeSet) -> Result<usize, PercentError> {
+    in_place(buf, length, set, |c, bytes| c.encode_utf8(bytes).len(), char::from)
+}
+
+// the same for UTF-8 bytes
+pub fn encode_in_place(buf: &mut [u8], length: usize, set: EncodeSet) -> Result<usize, PercentError> {
+    in_place(
+        buf,
+        length,
+        set,
+        |b, bytes| {
+            bytes[0] = b;
+            1
+        },
+        |b| b,
+    )
+}
+
+// every item grows, so it is written from the end of the result back, never over an item not read yet
+fn in_place<T: Copy>(
+    buf: &mut [T],
+    length: usize,
+    set: EncodeSet,
+    bytes_of: fn(T, &mut [u8; 4]) -> usize,
+    from_byte: fn(u8) -> T,
+) -> Result<usize, PercentError> {
+    if length > buf.len() {
+        return Err(PercentError::NoRoom { needed: length, available: buf.len() });
+    }
+    let mut bytes = [0u8; 4];
+    let mut needed = 0;
+    for &item in &buf[..length] {
+        let n = bytes_of(item, &mut bytes);
+        needed += bytes[..n].iter().map(|&b| set.encoded_len(b)).sum::<usize>();
+    }
+    if needed > buf.len() {
+        return Err(PercentError::NoRoom { needed, available: buf.len() });
+    }
+    let mut back = needed;
+    let mut encoded = Vec::with_capacity(12);
+    for i in (0..length).rev() {
+        let n = bytes_of(buf[i], &mut bytes);
+        encoded.clear();
+        for &b in &bytes[..n] {
+            set.encode_byte(b, &mut encoded);
+        }
+        back -= encoded.len();
+        for (j, &b) in encoded.iter().enumerate() {
+            buf[back + j] = from_byte(b);
+        }
+    }
+    Ok(needed)
+}
+
+// encodes everything written to it into 'inner'
+pub struct EncodeWriter<W: Write> {
+    inner: W,
+    set: EncodeSet,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EncodeWriter<W> {
+    pub fn new(inner: W, set: EncodeSet) -> EncodeWriter<W> {
+        EncodeWriter { inner, set, buffer: Vec::new() }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for EncodeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.clear();
+        for &b in buf {
+            self.set.encode_byte(b, &mut self.buffer);
+        }
+        self.inner.write_all(&self.buffer)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// reads the encoded bytes of 'inner'
+pub struct EncodeReader<R: Read> {
+    inner: R,
+    set: EncodeSet,
+    encoded: Vec<u8>,
+    position: usize, // in 'encoded', what is before was read
+}
+
+impl<R: Read> EncodeReader<R> {
+    pub fn new(inner: R, set: EncodeSet) -> EncodeReader<R> {
+        EncodeReader { inner, set, encoded: Vec::new(), position: 0 }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for EncodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.encoded.len() {
+            let mut chunk = [0u8; 1024];
+            let n = self.inner.read(&mut chunk)?;
+            self.encoded.clear();
+            self.position = 0;
+            for &b in &chunk[..n] {
+                self.set.encode_byte(b, &mut self.encoded);
+            }
+        }
+        let n = buf.len().min(self.encoded.len() - self.position);
+        buf[..n].copy_from_slice(&self.encoded[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+fn to_utf8(text: &str, bytes: Vec<u8>) -> Result<String, PercentError> {
+    String::from_utf8(bytes).map_err(|e| {
+        // back from the decoded bytes to the input, an escape is three bytes of it
+        let decoded = e.utf8_error().valid_up_to();
+        let input = text.as_bytes();
+        let mut position = 0;
+        for _ in 0..decoded {
+            position += if input[position] == b'%' { 3 } else { 1 };
+        }
+        PercentError::InvalidUtf8 { position }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_with_sets() {
+        assert_eq!(encode("a b/ü", EncodeSet::PATH), "a%20b/%C3%BC");
+        assert_eq!(encode("a b/ü", EncodeSet::COMPONENT), "a%20b%2F%C3%BC");
+        let query = format!("q={}", encode("a b&c=d+e?/", EncodeSet::QUERY_COMPONENT));
+        assert_eq!(query, "q=a%20b%26c%3Dd%2Be?/");
+        assert_eq!(encode("a b&c+d~", EncodeSet::FORM), "a+b%26c%2Bd%7E");
+        assert_eq!(encode("_-.~", EncodeSet::keeping(b"")), "%5F%2D%2E%7E");
+        assert!(!EncodeSet::PATH.encodes(b'@') && EncodeSet::PATH.encodes(b'#'));
+    }
+
+    #[test]
+    fn decode_and_errors() {
+        assert_eq!(decode("a%20b/%c3%bc"), Ok("a b/ü".to_string()));
+        assert_eq!(decode("a+b"), Ok("a+b".to_string()));
+        assert_eq!(decode_form("a+b%2B"), Ok("a b+".to_string()));
+        assert_eq!(decode("a%2"), Err(PercentError::InvalidEscape { position: 1 }));
+        assert_eq!(decode("ab%zz"), Err(PercentError::InvalidEscape { position: 2 }));
+        assert_eq!(decode("ü%C3"), Err(PercentError::InvalidUtf8 { position: 2 }));
+        assert_eq!(decode_bytes("%FF", false), Ok(vec![0xFF]));
+        let text = "съешь же ещё этих мягких французских булок, да выпей чаю";
+        assert_eq!(decode_form(&encode(text, EncodeSet::FORM)), Ok(text.to_string()));
+    }
+
+    #[test]
+    fn in_place_uses_the_garbage_tail() {
+        let mut buf: Vec<char> = "a bü     !!!".chars().collect();
+        assert_eq!(encode_chars_in_place(&mut buf, 4, EncodeSet::PATH), Ok(11));
+        assert_eq!(buf[..11].iter().collect::<String>(), "a%20b%C3%BC");
+        let mut buf = b"a b  ".to_vec();
+        assert_eq!(encode_in_place(&mut buf, 3, EncodeSet::FORM), Ok(3));
+        assert_eq!(&buf[..3], b"a+b");
+        assert_eq!(encode_in_place(&mut buf, 3, EncodeSet::COMPONENT), Ok(5));
+        assert_eq!(&buf, b"a%2Bb");
+        let mut buf = b"a b ".to_vec();
+        assert_eq!(encode_in_place(&mut buf, 3, EncodeSet::PATH), Err(PercentError::NoRoom { needed: 5, available: 4 }));
+        assert_eq!(&buf, b"a b ");
+    }
+
+    #[test]
+    fn streaming() {
+        let mut writer = EncodeWriter::new(Vec::new(), EncodeSet::PATH);
+        writer.write_all("a b".as_bytes()).unwrap();
+        writer.write_all("/ü".as_bytes()).unwrap();
+        assert_eq!(writer.into_inner(), b"a%20b/%C3%BC");
+
+        let text = "ü ".repeat(1000);
+        let mut reader = EncodeReader::new(text.as_bytes(), EncodeSet::FORM);
+        let mut encoded = String::new();
+        reader.read_to_string(&mut encoded).unwrap();
+        assert_eq!(encoded, "%C3%BC+".repeat(1000));
+    }
+}
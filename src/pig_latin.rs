@@ -0,0 +1,245 @@
+// pig latin which can be read back:
+//     to_pig_latin("first apple, string!") == "irst-fay apple-hay, ing-stray!"
+//     from_pig_latin("irst-fay apple-hay, ing-stray!") == "first apple, string!"
+// A word is a run of graphemes which start with a letter, everything between words is kept as it is.
+// The consonants before the first vowel move to the end with "ay", a word starting with a vowel gets "-hay",
+// a word without vowels is all consonants: "rhythm" -> "-rhythmay". Letters keep their case, the suffix is
+// upper case for upper case words: "STRING" -> "ING-STRAY".
+// A word starting with "h" and a vowel would look as a vowel word ("ham" -> "am-hay" like "am"), so a cluster
+// of only "h" gets one more: "ham" -> "am-hhay".
+// Vowels are looked up by the script of the letter, there are vowel sets for Latin, Cyrillic and kana,
+// letters of other scripts (kanji) are consonants.
+
+use std::collections::{HashMap, HashSet};
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    Latin,
+    Cyrillic,
+    Kana, // hiragana and katakana
+}
+
+impl Script {
+    pub fn of(c: char) -> Option<Script> {
+        match c as u32 {
+            0x0041..=0x024F => Some(Script::Latin), // Basic Latin to Latin Extended-B
+            0x0400..=0x052F => Some(Script::Cyrillic),
+            0x3040..=0x30FF => Some(Script::Kana),
+            _ => None,
+        }
+    }
+}
+
+pub struct Translator {
+    vowels: HashMap<Script, HashSet<char>>, // lower case
+}
+
+impl Translator {
+    pub fn new() -> Translator {
+        Translator::empty()
+            .with_vowels(Script::Latin, "aeiouáéíóúàèìòùâêîôûäëïöüåæøœ")
+            .with_vowels(Script::Cyrillic, "аеёиоуыэюяіїє")
+            .with_vowels(Script::Kana, "あいうえおぁぃぅぇぉアイウエオァィゥェォ")
+    }
+
+    // no vowels at all, every word is a cluster
+    pub fn empty() -> Translator {
+        Translator { vowels: HashMap::new() }
+    }
+
+    // replaces vowels of the script
+    pub fn with_vowels(mut self, script: Script, vowels: &str) -> Translator {
+        self.vowels.insert(script, vowels.chars().flat_map(char::to_lowercase).collect());
+        self
+    }
+
+    pub fn is_vowel(&self, grapheme: &str) -> bool {
+        // "é" can be 'e' and a combining accent, the first char tells
+        grapheme.chars().next().is_some_and(|c| {
+            Script::of(c)
+                .and_then(|script| self.vowels.get(&script))
+                .is_some_and(|vowels| c.to_lowercase().all(|l| vowels.contains(&l)))
+        })
+    }
+
+    pub fn to_pig_latin(&self, text: &str) -> String {
+        let graphemes: Vec<&str> = text.graphemes(true).collect();
+        let mut result = String::with_capacity(text.len() * 2);
+        let mut i = 0;
+        while i < graphemes.len() {
+            let end = word_end(&graphemes, i);
+            if end == i {
+                result.push_str(graphemes[i]);
+                i += 1;
+            } else {
+                self.translate_word(&graphemes[i..end], &mut result);
+                i = end;
+            }
+        }
+        result
+    }
+
+    // text which is not a pig latin word is kept as it is
+    pub fn from_pig_latin(&self, text: &str) -> String {
+        let graphemes: Vec<&str> = text.graphemes(true).collect();
+        let mut result = String::with_capacity(text.len());
+        let mut i = 0;
+        while i < graphemes.len() {
+            if let Some(end) = self.decode_word(&graphemes, i, &mut result) {
+                i = end;
+            } else {
+                // a whole run of letters, a word can't start in the middle of it
+                let end = word_end(&graphemes, i).max(i + 1);
+                for g in &graphemes[i..end] {
+                    result.push_str(g);
+                }
+                i = end;
+            }
+        }
+        result
+    }
+
+    fn translate_word(&self, word: &[&str], result: &mut String) {
+        let upper = is_upper_case(word);
+        let ay = if upper { "AY" } else { "ay" };
+        let vowel = word.iter().position(|g| self.is_vowel(g)).unwrap_or(word.len());
+        if vowel == 0 {
+            push_all(result, word);
+            result.push('-');
+            result.push_str(if upper { "H" } else { "h" });
+        } else {
+            let (cluster, rest) = word.split_at(vowel);
+            push_all(result, rest);
+            result.push('-');
+            push_all(result, cluster);
+            if cluster.iter().all(|g| is_h(g)) {
+                result.push_str(if upper { "H" } else { "h" });
+            }
+        }
+        result.push_str(ay);
+    }
+
+    // "<rest>-<cluster>ay" from 'start', returns the end of the encoded word
+    fn decode_word(&self, graphemes: &[&str], start: usize, result: &mut String) -> Option<usize> {
+        let dash = word_end(graphemes, start);
+        if graphemes.get(dash) != Some(&"-") {
+            return None;
+        }
+        let (rest, end) = (&graphemes[start..dash], word_end(graphemes, dash + 1));
+        let suffix = &graphemes[dash + 1..end];
+        if suffix.len() < 3 || !suffix[suffix.len() - 2].eq_ignore_ascii_case("a") || !suffix[suffix.len() - 1].eq_ignore_ascii_case("y") {
+            return None;
+        }
+        let mut cluster = &suffix[..suffix.len() - 2];
+        if cluster.iter().any(|g| self.is_vowel(g)) || rest.first().is_some_and(|g| !self.is_vowel(g)) {
+            return None;
+        }
+        if cluster.iter().all(|g| is_h(g)) {
+            if rest.is_empty() {
+                return None; // never made by 'to_pig_latin', "h" is always escaped
+            }
+            cluster = &cluster[..cluster.len() - 1]; // the added one, a vowel word for "h"
+        }
+        push_all(result, cluster);
+        push_all(result, rest);
+        Some(end)
+    }
+}
+
+impl Default for Translator {
+    fn default() -> Translator {
+        Translator::new()
+    }
+}
+
+pub fn to_pig_latin(text: &str) -> String {
+    Translator::new().to_pig_latin(text)
+}
+
+pub fn from_pig_latin(text: &str) -> String {
+    Translator::new().from_pig_latin(text)
+}
+
+fn is_letter(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(char::is_alphabetic)
+}
+
+fn is_h(grapheme: &str) -> bool {
+    grapheme == "h" || grapheme == "H"
+}
+
+// at least two letters and no lower case ones: "STRING", but not "I"
+fn is_upper_case(word: &[&str]) -> bool {
+    let cased: Vec<char> = word.iter().flat_map(|g| g.chars()).filter(|c| c.is_lowercase() || c.is_uppercase()).collect();
+    cased.len() > 1 && cased.iter().all(|c| c.is_uppercase())
+}
+
+fn word_end(graphemes: &[&str], start: usize) -> usize {
+    start + graphemes[start..].iter().take_while(|g| is_letter(g)).count()
+}
+
+fn push_all(result: &mut String, graphemes: &[&str]) {
+    for g in graphemes {
+        result.push_str(g);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(text: &str, pig_latin: &str) {
+        assert_eq!(to_pig_latin(text), pig_latin);
+        assert_eq!(from_pig_latin(pig_latin), text);
+    }
+
+    #[test]
+    fn consonant_clusters_and_vowels() {
+        round_trip("first apple", "irst-fay apple-hay");
+        round_trip("string", "ing-stray");
+        round_trip("rhythm", "-rhythmay");
+        round_trip("ham am", "am-hhay am-hay");
+    }
+
+    #[test]
+    fn punctuation_case_and_whitespace_are_kept() {
+        round_trip("Hello,  World!\n\tIt's a STRING.", "ello-Hhay,  orld-Way!\n\tIt-hay'-say a-hay ING-STRAY.");
+        round_trip("well-known x-ay -apple", "ell-way-own-knay -xay-ay-hay -apple-hay");
+        round_trip("", "");
+    }
+
+    #[test]
+    fn graphemes_are_not_split() {
+        // 'e' and a combining acute accent is one vowel
+        round_trip("e\u{301}te\u{301} café", "e\u{301}te\u{301}-hay afé-cay");
+    }
+
+    #[test]
+    fn russian_sample() {
+        round_trip(
+            "съешь еще этих мягких французских булок",
+            "ешь-съay еще-hay этих-hay ягких-мay анцузских-фрay улок-бay",
+        );
+        round_trip("Съешь же ещё!", "ешь-Съay е-жay ещё-hay!");
+    }
+
+    #[test]
+    fn japanese_sample() {
+        // ideographic spaces stay, kanji are consonants
+        round_trip("この　世界は　いいと　訝しまない", "-このay　-世界はay　いいと-hay　い-訝しまなay");
+    }
+
+    #[test]
+    fn vowels_are_configurable() {
+        let translator = Translator::new().with_vowels(Script::Latin, "aeiouy");
+        assert_eq!(translator.to_pig_latin("rhythm"), "ythm-rhay");
+        assert_eq!(translator.from_pig_latin("ythm-rhay"), "rhythm");
+        assert_eq!(Translator::empty().to_pig_latin("abc"), "-abcay");
+    }
+
+    #[test]
+    fn other_text_is_not_decoded() {
+        assert_eq!(from_pig_latin("plain text, -hay x-y"), "plain text, -hay x-y");
+    }
+}
@@ -2,6 +2,7 @@ use std::rc::{Rc, Weak};
 use std::cell::RefCell;
 extern crate myrust;
 use myrust::Verbose;
+use myrust::leaks::LeakGuard;
 
 // RefCell specifies what field we want to able to modify in an immutable object
 #[derive(Debug)]
@@ -29,6 +30,12 @@ struct Node<T> {
 }
 
 fn main() {
+    let _leaks = LeakGuard::new(); // prints values which are not dropped when 'main' returns: Verbose 5 and 10
+    strong_cycle();
+    weak_parent_tree();
+}
+
+fn strong_cycle() {
     {
         // memory leak
         let a = Rc::new(Cons(Verbose::new(5), RefCell::new(Rc::new(Nil))));
@@ -49,6 +56,9 @@ fn main() {
         // now 'println!("{}", a.tail());' will overflow the stack
         println!("Verbose 5, 10 were not dropped, memory leak due to strong cross referencing");
     }
+}
+
+fn weak_parent_tree() {
     {
         let leaf = Rc::new(Node {
             value: Verbose::new(11),
@@ -71,3 +81,17 @@ fn main() {
         println!("Verbose 11 and 12 will be dropped, no memory leak, due to weak cross referencing");
     }
 }
+
+#[test]
+fn strong_cycle_leaks_both_values() {
+    let guard = LeakGuard::new();
+    strong_cycle();
+    guard.assert_leaks(&[5, 10]);
+}
+
+#[test]
+fn weak_parent_reference_does_not_leak() {
+    let guard = LeakGuard::new();
+    weak_parent_tree();
+    guard.assert_no_leaks();
+}
@@ -0,0 +1,214 @@
+// a restaurant driven by commands from stdin:
+//     arrive 4            a party of 4 joins the waitlist
+//     seat                parties from the waitlist take free tables
+//     order 3 Rye Soup    the party at table 3 orders, the kitchen cooks and the waiter serves
+//     pay 3 15%           the party at table 3 pays with a tip and leaves
+//     wait 20             20 minutes pass
+//     report              totals of the day
+// Every successful command is appended to the state file, the next run replays it and continues the day:
+//     cargo run --bin restaurant -- monday.txt
+
+extern crate myrust;
+
+use myrust::back_of_house::kitchen::Kitchen;
+use myrust::clock::{Clock, ManualClock};
+use myrust::front_of_house::hosting::{table_id, table_number, Host, TableId};
+use myrust::front_of_house::serving::{money, Bill, Cents, Menu, OrderState, Tip, Waiter};
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+const TABLES: [u32; 5] = [2, 2, 4, 4, 6]; // tables 1 to 5 for guests
+const MINUTE: Duration = Duration::from_secs(60);
+
+struct Restaurant {
+    clock: Arc<ManualClock>, // time since opening, moved by 'wait'
+    host: Host,
+    waiter: Waiter,
+    kitchen: Kitchen,
+    bills: Vec<Bill>,
+}
+
+impl Restaurant {
+    fn new() -> Restaurant {
+        let clock = Arc::new(ManualClock::new());
+        Restaurant {
+            host: Host::new(&TABLES),
+            waiter: Waiter::new(Menu::house()),
+            kitchen: Kitchen::new(2, clock.clone()),
+            clock,
+            bills: vec![],
+        }
+    }
+
+    // replays a state file, line numbers of the file are in errors
+    fn restore(state: &str) -> Result<Restaurant, String> {
+        let mut restaurant = Restaurant::new();
+        for (i, line) in state.lines().enumerate() {
+            restaurant.execute(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        }
+        Ok(restaurant)
+    }
+
+    fn execute(&mut self, line: &str) -> Result<String, String> {
+        let line = line.split('#').next().unwrap(); // a comment
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let now = self.clock.now();
+        match words.as_slice() {
+            [] => Ok(String::new()),
+            ["arrive", size] => {
+                let party = self.host.add_to_waitlist(parse(size)?, now).map_err(|e| e.to_string())?;
+                let wait = self.host.estimated_wait(party, now).map_err(|e| e.to_string())?;
+                Ok(format!("party {} is waiting, about {} min", party, wait.as_secs() / 60))
+            }
+            ["seat"] => {
+                let seated = self.host.seat_all(now);
+                if seated.is_empty() {
+                    return Ok("nobody can be seated".to_string());
+                }
+                let lines: Vec<String> = seated.iter()
+                    .map(|s| format!("party {} of {} is at table {}", s.party, s.size, table_number(s.table)))
+                    .collect();
+                Ok(lines.join("\n"))
+            }
+            ["order", table, items @ ..] => {
+                let table = self.occupied_table(table)?;
+                let order = self.waiter.take_order(table, items).map_err(|e| e.to_string())?;
+                self.kitchen.place(&mut self.waiter, order).map_err(|e| e.to_string())?;
+                self.kitchen.serve_all(&mut self.waiter).map_err(|e| e.to_string())?;
+                Ok(format!("order {} is served at table {}", order, table_number(table)))
+            }
+            ["pay", table, tip @ ..] => {
+                let table = self.occupied_table(table)?;
+                let tip = match tip {
+                    [] => Tip::Nothing,
+                    [percent] if percent.ends_with('%') => Tip::Percent(parse(percent.trim_end_matches('%'))?),
+                    _ => return Err("usage: pay <table> [<tip>%]".to_string()),
+                };
+                let orders: Vec<_> = self.waiter.orders()
+                    .filter(|o| o.table == table && o.state() == OrderState::Served)
+                    .map(|o| o.id)
+                    .collect();
+                let mut receipts = vec![];
+                for order in orders {
+                    let bill = self.waiter.take_payment(order, tip).map_err(|e| e.to_string())?;
+                    receipts.push(bill.to_string());
+                    self.bills.push(bill);
+                }
+                self.host.leave(table, now).map_err(|e| e.to_string())?;
+                receipts.push(format!("table {} is free", table_number(table)));
+                Ok(receipts.join("\n"))
+            }
+            ["wait", minutes] => {
+                self.clock.advance(MINUTE * parse(minutes)?);
+                Ok(format!("it is {} since opening", clock_time(self.clock.now())))
+            }
+            ["report"] => Ok(self.report()),
+            _ => Err(format!("unknown command '{}'", line.trim())),
+        }
+    }
+
+    // the table with the number a guest gives
+    fn occupied_table(&self, number: &str) -> Result<TableId, String> {
+        let number: usize = parse(number)?;
+        match table_id(number).and_then(|table| self.host.tables().get(table)) {
+            Some(t) if !t.is_free() => Ok(t.id),
+            Some(_) => Err(format!("table {} is free", number)),
+            None => Err(format!("there is no table {}", number)),
+        }
+    }
+
+    fn report(&self) -> String {
+        let now = self.clock.now();
+        let occupied = self.host.tables().iter().filter(|t| !t.is_free()).count();
+        let sum = |f: &dyn Fn(&Bill) -> Cents| -> Cents { self.bills.iter().map(f).sum() };
+        let kitchen = self.kitchen.report();
+        [
+            format!("time: {}", clock_time(now)),
+            format!("waiting: {} parties", self.host.waitlist().len()),
+            format!("tables: {} of {} occupied, utilization {:.0}%",
+                    occupied, TABLES.len(), self.host.utilization(now) * 100.0),
+            format!("orders: {}, paid {}", self.waiter.orders().count(), self.bills.len()),
+            format!("revenue: subtotal {}, tax {}, tips {}, total {}",
+                    money(sum(&|b| b.subtotal())), money(sum(&|b| b.tax())), money(sum(&|b| b.tip)), money(sum(&|b| b.total()))),
+            format!("kitchen: {} served, {} min per ticket", kitchen.served, kitchen.average_ticket_time.as_secs() / 60),
+        ].join("\n")
+    }
+}
+
+fn parse<T: std::str::FromStr>(word: &str) -> Result<T, String> {
+    word.parse().map_err(|_| format!("'{}' is not a number", word))
+}
+
+fn clock_time(time: Duration) -> String {
+    let minutes = time.as_secs() / 60;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+fn main() {
+    let path = env::args().nth(1).unwrap_or_else(|| "restaurant.txt".to_string());
+    let mut restaurant = match fs::read_to_string(&path) {
+        Ok(state) => match Restaurant::restore(&state) {
+            Ok(restaurant) => {
+                println!("The day is restored from {}", path);
+                restaurant
+            }
+            Err(e) => {
+                println!("Cannot restore {}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        Err(_) => Restaurant::new(), // a new day
+    };
+    let mut state = OpenOptions::new().create(true).append(true).open(&path).expect("Cannot open the state file");
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        let line = line.expect("Cannot read input");
+        match restaurant.execute(&line) {
+            Ok(output) => {
+                if !output.is_empty() {
+                    println!("{}", output);
+                }
+                // 'report' does not change anything, it is not saved
+                if !line.trim().is_empty() && line.trim() != "report" {
+                    writeln!(state, "{}", line.trim()).expect("Cannot save the state");
+                }
+            }
+            Err(e) => println!("Error: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: &str = "arrive 4\narrive 2\nseat\nwait 10\norder 3 Rye Soup\norder 1 Salad\nwait 30\npay 3 10%\nreport\n";
+
+    #[test]
+    fn a_day_is_replayed_with_the_same_totals() {
+        let mut restaurant = Restaurant::new();
+        let outputs: Vec<String> = DAY.lines().map(|l| restaurant.execute(l).unwrap()).collect();
+        assert_eq!(outputs[2], "party 1 of 4 is at table 3\nparty 2 of 2 is at table 1");
+        assert!(outputs[7].contains("Total                        12.98"), "{}", outputs[7]);
+        let report = Restaurant::restore(DAY).unwrap().report();
+        assert_eq!(report, outputs[8]);
+        assert!(report.contains("orders: 2, paid 1\nrevenue: subtotal 11.00, tax 0.88, tips 1.10, total 12.98"), "{}", report);
+    }
+
+    #[test]
+    fn errors_are_reported_and_change_nothing() {
+        let mut restaurant = Restaurant::new();
+        assert_eq!(restaurant.execute("arrive 9"), Err("no table for a party of 9, the largest table is for 6".to_string()));
+        assert_eq!(restaurant.execute("order 2 Rye"), Err("table 2 is free".to_string()));
+        assert_eq!(restaurant.execute("order 0 Rye"), Err("there is no table 0".to_string())); // tables are numbered from 1
+        assert_eq!(restaurant.execute("dance"), Err("unknown command 'dance'".to_string()));
+        restaurant.execute("arrive 2").unwrap();
+        restaurant.execute("seat").unwrap();
+        assert_eq!(restaurant.execute("order 1 Pizza"), Err("there is no 'Pizza' in the menu".to_string()));
+        assert_eq!(Restaurant::restore("arrive 2\npay x").err(), Some("line 2: 'x' is not a number".to_string()));
+    }
+}
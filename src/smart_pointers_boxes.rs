@@ -45,7 +45,7 @@ fn main() {
     }
     {
         // move value from stack to heap
-        let v = Verbose { id: 1 };
+        let v = Verbose::new(1);
         let b = Box::new(v); // ownership is taken from 'v'
         compilation_error!(
             println!("try to use x {}", v.id); // borrow of moved value: `v`
@@ -119,7 +119,7 @@ fn main() {
 
         let x: &mut i32 = &mut a; // A deref_mut, value is 1, rule "From &mut T to &mut U when T: DerefMut<Target=U>"
         compilation_error!(
-            x = &3; // types differ in mutability, expected mutable reference `&mut i32`, found reference `&i32`
+            x = &3; // types differ in mutability, expected mutable reference `&mut _`
         );
 
         a.value = 4;
@@ -144,43 +144,14 @@ fn main() {
     }
 }
 
-trait Messenger {
-    fn send(&self, msg: &str);
-}
-struct LimitTracker<'a, T: Messenger> {
-    messenger: &'a T,
-    value: usize,
-    max: usize,
-}
-impl<'a, T: Messenger> LimitTracker<'a, T> {
-    fn new(messenger: &T, max: usize) -> LimitTracker<T> {
-        LimitTracker {
-            messenger,
-            value: 0,
-            max
-        }
-    }
-
-    fn set_value(&mut self, value: usize) {
-        self.value = value;
-
-        let percentage_of_max = self.value as f64 / self.max as f64;
-
-        if percentage_of_max >= 1.0 {
-            self.messenger.send("Error: You are over your quota!");
-        } else if percentage_of_max >= 0.9 {
-            self.messenger
-                .send("Urgent warning: You've used up over 90% of your quota!");
-        } else if percentage_of_max >= 0.75 {
-            self.messenger
-                .send("Warning: You've used up over 75% of your quota!");
-        }
-    }
-}
+// 'Messenger' and 'LimitTracker' are in src/limits.rs, the tracker sends a warning once when a threshold is crossed,
+// and it can have its own thresholds, messages, severities and a hysteresis;
+// the test below shows how RefCell lets a mock messenger record messages through '&self'
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use myrust::limits::{LimitTracker, Messenger, Severity, Threshold};
 
     struct MockMessenger {
         sent_messages: RefCell<Vec<String>>
@@ -192,8 +163,8 @@ mod tests {
         }
     }
     impl Messenger for MockMessenger {
-        fn send(&self, msg: &str) {
-            self.sent_messages.borrow_mut().push(msg.to_owned());
+        fn send(&self, severity: Severity, msg: &str) {
+            self.sent_messages.borrow_mut().push(format!("{}: {}", severity, msg));
         }
     }
 
@@ -205,5 +176,39 @@ mod tests {
         limit_tracker.set_value(80);
         // The RefCell<T> keeps track of how many Ref<T> and RefMut<T> smart pointers are currently active.
         assert_eq!(mock_messenger.sent_messages.borrow().len(), 1);
+
+        // every warning once on the way up, not again while the value stays over it
+        for value in [85, 90, 95, 100, 120, 99, 100, 50] {
+            limit_tracker.set_value(value);
+        }
+        assert_eq!(*mock_messenger.sent_messages.borrow(), vec![
+            "warning: Warning: You've used up over 75% of your quota!",
+            "urgent: Urgent warning: You've used up over 90% of your quota!",
+            "error: Error: You are over your quota!",
+            "error: Error: You are over your quota!", // 99 was below 100% and there is no hysteresis
+            "info: Recovered: You are back under your quota",
+        ]);
+    }
+
+    #[test]
+    fn it_sends_custom_alerts_again_only_after_the_hysteresis() {
+        let mock_messenger = MockMessenger::new();
+        let mut limit_tracker = LimitTracker::new(&mock_messenger, 1000)
+            .with_thresholds(vec![
+                Threshold::new(80, Severity::Warning, "80% of disk"),
+                Threshold::new(95, Severity::Urgent, "disk almost full"),
+            ])
+            .with_hysteresis(5)
+            .with_recovered_message("disk is fine");
+        for value in [800, 760, 799, 960, 899, 960, 900, 800, 749, 800] {
+            limit_tracker.set_value(value);
+        }
+        assert_eq!(*mock_messenger.sent_messages.borrow(), vec![
+            "warning: 80% of disk",
+            "urgent: disk almost full",
+            "urgent: disk almost full", // 899 was below 95% - 5%
+            "info: disk is fine",
+            "warning: 80% of disk",
+        ]);
     }
 }
@@ -71,7 +71,7 @@ fn main() {
         }
         // when owning referee 'v' goes out of scope, Verbose.id == 1 dies, so  'weak_ref' becomes invalid
         runtime_error!(
-            // thread 'main' panicked at 'called `Option::unwrap()` on a `None` value'
+            "called `Option::unwrap()` on a `None` value", // thread 'main' panicked at ...
             println!("Weak references points to {:?}", weak_ref.upgrade().unwrap());
         );
     }
@@ -0,0 +1,216 @@
+// descriptive statistics of integer and float slices, results are f64:
+//     let data = [1, 6, 2, 8, 5, 2, 9, 7, 4];
+//     mean(&data)   == Ok(4.888...)
+//     median(&data) == Ok(5.0)
+//     modes(&data)  == Ok(vec![2])
+// Empty data and NaN give errors instead of panics or garbage.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+pub mod streaming; // for data which does not fit in memory, src/stats/streaming.rs
+
+// a number which can be put in f64, maybe with rounding (i64, u64)
+pub trait Number: Copy + PartialOrd + fmt::Debug {
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! number {
+    ($($t:ty)*) => {
+        $(impl Number for $t {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        })*
+    };
+}
+
+number!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize f32 f64);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsError {
+    Empty,
+    NaN,
+    NotEnoughData { needed: usize, got: usize },
+    InvalidPercentile(f64), // not in 0..=100
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StatsError::Empty => write!(f, "no data"),
+            StatsError::NaN => write!(f, "data contains NaN"),
+            StatsError::NotEnoughData { needed, got } => write!(f, "needs at least {} values, got {}", needed, got),
+            StatsError::InvalidPercentile(p) => write!(f, "percentile {} is not between 0 and 100", p),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FiveNumberSummary {
+    pub min: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub max: f64,
+}
+
+impl FiveNumberSummary {
+    pub fn interquartile_range(&self) -> f64 {
+        self.q3 - self.q1
+    }
+}
+
+pub fn mean<T: Number>(data: &[T]) -> Result<f64, StatsError> {
+    let values = checked(data)?;
+    Ok(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+// the middle value, or the mean of two middle values when the length is even
+pub fn median<T: Number>(data: &[T]) -> Result<f64, StatsError> {
+    percentile(data, 50.0)
+}
+
+// all most frequent values in ascending order
+pub fn modes<T: Number>(data: &[T]) -> Result<Vec<T>, StatsError> {
+    checked(data)?;
+    let sorted = sorted(data);
+    let mut modes = vec![];
+    let mut best = 0;
+    let mut i = 0;
+    while i < sorted.len() {
+        let run = sorted[i..].iter().take_while(|&&v| v == sorted[i]).count();
+        if run > best {
+            best = run;
+            modes.clear();
+        }
+        if run == best {
+            modes.push(sorted[i]);
+        }
+        i += run;
+    }
+    Ok(modes)
+}
+
+// of the population, divided by n
+pub fn variance<T: Number>(data: &[T]) -> Result<f64, StatsError> {
+    let values = checked(data)?;
+    Ok(squared_deviations(&values) / values.len() as f64)
+}
+
+// of a sample, divided by n - 1
+pub fn sample_variance<T: Number>(data: &[T]) -> Result<f64, StatsError> {
+    let values = checked(data)?;
+    if values.len() < 2 {
+        return Err(StatsError::NotEnoughData { needed: 2, got: values.len() });
+    }
+    Ok(squared_deviations(&values) / (values.len() - 1) as f64)
+}
+
+pub fn std_dev<T: Number>(data: &[T]) -> Result<f64, StatsError> {
+    variance(data).map(f64::sqrt)
+}
+
+pub fn sample_std_dev<T: Number>(data: &[T]) -> Result<f64, StatsError> {
+    sample_variance(data).map(f64::sqrt)
+}
+
+// 'p' from 0 to 100, linear interpolation between the closest ranks
+pub fn percentile<T: Number>(data: &[T], p: f64) -> Result<f64, StatsError> {
+    checked(data)?;
+    let sorted: Vec<f64> = sorted(data).into_iter().map(Number::to_f64).collect();
+    percentile_of_sorted(&sorted, p)
+}
+
+pub fn five_number_summary<T: Number>(data: &[T]) -> Result<FiveNumberSummary, StatsError> {
+    checked(data)?;
+    let sorted: Vec<f64> = sorted(data).into_iter().map(Number::to_f64).collect();
+    Ok(FiveNumberSummary {
+        min: sorted[0],
+        q1: percentile_of_sorted(&sorted, 25.0)?,
+        median: percentile_of_sorted(&sorted, 50.0)?,
+        q3: percentile_of_sorted(&sorted, 75.0)?,
+        max: sorted[sorted.len() - 1],
+    })
+}
+
+fn percentile_of_sorted(sorted: &[f64], p: f64) -> Result<f64, StatsError> {
+    if !(0.0..=100.0).contains(&p) {
+        return Err(StatsError::InvalidPercentile(p));
+    }
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    Ok(sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64))
+}
+
+fn checked<T: Number>(data: &[T]) -> Result<Vec<f64>, StatsError> {
+    if data.is_empty() {
+        return Err(StatsError::Empty);
+    }
+    let values: Vec<f64> = data.iter().map(|v| v.to_f64()).collect();
+    if values.iter().any(|v| v.is_nan()) {
+        return Err(StatsError::NaN);
+    }
+    Ok(values)
+}
+
+// data is checked, there is no NaN
+fn sorted<T: Number>(data: &[T]) -> Vec<T> {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    sorted
+}
+
+fn squared_deviations(values: &[f64]) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    values.iter().map(|v| (v - mean) * (v - mean)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_median_modes() {
+        let data = [1, 6, 2, 8, 5, 2, 9, 7, 4];
+        assert!((mean(&data).unwrap() - 44.0 / 9.0).abs() < 1e-12);
+        assert_eq!(median(&data), Ok(5.0));
+        assert_eq!(median(&[4u8, 1, 3, 2]), Ok(2.5));
+        assert_eq!(modes(&data), Ok(vec![2]));
+        assert_eq!(modes(&[3, 1, 2, 1, 2, 3, 4]), Ok(vec![1, 2, 3]));
+        assert_eq!(modes(&[0.5, 0.25]), Ok(vec![0.25, 0.5]));
+    }
+
+    #[test]
+    fn spread() {
+        let data = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        assert_eq!(variance(&data), Ok(4.0));
+        assert_eq!(std_dev(&data), Ok(2.0));
+        assert!((sample_variance(&data).unwrap() - 32.0 / 7.0).abs() < 1e-12);
+        assert_eq!(sample_std_dev(&[1i64]), Err(StatsError::NotEnoughData { needed: 2, got: 1 }));
+    }
+
+    #[test]
+    fn percentiles_and_summary() {
+        let data: Vec<u32> = (1..=11).collect();
+        assert_eq!(percentile(&data, 0.0), Ok(1.0));
+        assert_eq!(percentile(&data, 90.0), Ok(10.0));
+        assert_eq!(percentile(&[1, 2], 25.0), Ok(1.25));
+        assert_eq!(percentile(&data, 101.0), Err(StatsError::InvalidPercentile(101.0)));
+        let summary = five_number_summary(&data).unwrap();
+        assert_eq!(summary, FiveNumberSummary { min: 1.0, q1: 3.5, median: 6.0, q3: 8.5, max: 11.0 });
+        assert_eq!(summary.interquartile_range(), 5.0);
+    }
+
+    #[test]
+    fn empty_and_nan_are_errors() {
+        let empty: [i32; 0] = [];
+        assert_eq!(mean(&empty), Err(StatsError::Empty));
+        assert_eq!(modes(&empty), Err(StatsError::Empty));
+        assert_eq!(five_number_summary(&empty), Err(StatsError::Empty));
+        assert_eq!(median(&[1.0, f64::NAN]), Err(StatsError::NaN));
+        assert_eq!(StatsError::Empty.to_string(), "no data");
+    }
+}
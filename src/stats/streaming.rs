@@ -0,0 +1,339 @@
+// statistics of data which is seen once and not kept, memory does not grow with the data:
+//     let mut summary = StreamingSummary::new(&[50.0, 90.0, 99.0])?;
+//     for x in numbers {
+//         summary.push(x)?;
+//     }
+//     println!("{}", summary);
+// Mean and variance are exact (Welford's algorithm), quantiles are estimated with the P² algorithm
+// of R. Jain and I. Chlamtac, which keeps five markers per quantile, the first 1024 values give exact quantiles.
+
+use std::fmt;
+use stats::{percentile, StatsError};
+
+// count, mean and variance updated with every value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Accumulator {
+    count: u64,
+    mean: f64,
+    m2: f64, // sum of squared deviations from the current mean
+    min: f64,
+    max: f64,
+}
+
+impl Accumulator {
+    pub fn new() -> Accumulator {
+        Accumulator { count: 0, mean: 0.0, m2: 0.0, min: f64::INFINITY, max: f64::NEG_INFINITY }
+    }
+
+    pub fn push(&mut self, x: f64) -> Result<(), StatsError> {
+        if x.is_nan() {
+            return Err(StatsError::NaN);
+        }
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        Ok(())
+    }
+
+    // as if all values of 'other' were pushed to this one, for data read in parts
+    pub fn merge(&mut self, other: &Accumulator) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * other.count as f64 / count as f64;
+        self.count = count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> Result<f64, StatsError> {
+        self.non_empty().map(|a| a.mean)
+    }
+
+    pub fn variance(&self) -> Result<f64, StatsError> {
+        self.non_empty().map(|a| a.m2 / a.count as f64)
+    }
+
+    pub fn sample_variance(&self) -> Result<f64, StatsError> {
+        if self.count < 2 {
+            return Err(StatsError::NotEnoughData { needed: 2, got: self.count as usize });
+        }
+        Ok(self.m2 / (self.count - 1) as f64)
+    }
+
+    pub fn std_dev(&self) -> Result<f64, StatsError> {
+        self.variance().map(f64::sqrt)
+    }
+
+    pub fn min(&self) -> Result<f64, StatsError> {
+        self.non_empty().map(|a| a.min)
+    }
+
+    pub fn max(&self) -> Result<f64, StatsError> {
+        self.non_empty().map(|a| a.max)
+    }
+
+    fn non_empty(&self) -> Result<&Accumulator, StatsError> {
+        if self.count == 0 { Err(StatsError::Empty) } else { Ok(self) }
+    }
+}
+
+impl Default for Accumulator {
+    fn default() -> Accumulator {
+        Accumulator::new()
+    }
+}
+
+// estimate of one percentile, five markers whatever the number of values is
+#[derive(Debug, Clone, PartialEq)]
+pub struct P2Quantile {
+    p: f64, // from 0 to 1
+    count: usize,
+    heights: [f64; 5],   // marker values, the middle one is the estimate
+    positions: [f64; 5], // marker positions, from 1 to count
+    desired: [f64; 5],   // where markers should be
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    // 'percentile' from 0 to 100, as in 'stats::percentile'
+    pub fn new(percentile: f64) -> Result<P2Quantile, StatsError> {
+        if !(0.0..=100.0).contains(&percentile) {
+            return Err(StatsError::InvalidPercentile(percentile));
+        }
+        let p = percentile / 100.0;
+        Ok(P2Quantile {
+            p,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        })
+    }
+
+    pub fn percentile(&self) -> f64 {
+        self.p * 100.0
+    }
+
+    pub fn push(&mut self, x: f64) -> Result<(), StatsError> {
+        if x.is_nan() {
+            return Err(StatsError::NaN);
+        }
+        if self.count < 5 {
+            // the first five values are the markers
+            self.heights[self.count] = x;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            }
+            return Ok(());
+        }
+        self.count += 1;
+        let h = &mut self.heights;
+        // the cell of 'x' between markers, the extreme markers follow min and max
+        let k = if x < h[0] {
+            h[0] = x;
+            0
+        } else if x >= h[4] {
+            h[4] = x;
+            3
+        } else {
+            (0..4).find(|&i| x < h[i + 1]).unwrap()
+        };
+        for position in &mut self.positions[k + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments.iter()) {
+            *desired += increment;
+        }
+        for i in 1..4 {
+            let d = self.desired[i] - self.positions[i];
+            if (d >= 1.0 && self.positions[i + 1] - self.positions[i] > 1.0)
+                || (d <= -1.0 && self.positions[i - 1] - self.positions[i] < -1.0)
+            {
+                let d = d.signum();
+                let parabolic = self.parabolic(i, d);
+                self.heights[i] = if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                    parabolic
+                } else {
+                    self.linear(i, d)
+                };
+                self.positions[i] += d;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn estimate(&self) -> Result<f64, StatsError> {
+        match self.count {
+            0 => Err(StatsError::Empty),
+            n if n < 5 => percentile(&self.heights[..n], self.p * 100.0), // exact, there are few values
+            _ => Ok(self.heights[2]),
+        }
+    }
+
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + d / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, d: f64) -> f64 {
+        let j = if d > 0.0 { i + 1 } else { i - 1 };
+        self.heights[i] + d * (self.heights[j] - self.heights[i]) / (self.positions[j] - self.positions[i])
+    }
+}
+
+// P² is rough on a few values, up to this number of values they are kept and quantiles are exact
+const EXACT_LIMIT: usize = 1024;
+
+// an accumulator and a sketch for every requested percentile
+#[derive(Debug, Clone)]
+pub struct StreamingSummary {
+    accumulator: Accumulator,
+    quantiles: Vec<P2Quantile>,
+    first: Vec<f64>, // the first values, cleared after 'EXACT_LIMIT'
+}
+
+impl StreamingSummary {
+    pub fn new(percentiles: &[f64]) -> Result<StreamingSummary, StatsError> {
+        let quantiles = percentiles.iter().map(|&p| P2Quantile::new(p)).collect::<Result<Vec<_>, _>>()?;
+        Ok(StreamingSummary { accumulator: Accumulator::new(), quantiles, first: vec![] })
+    }
+
+    pub fn push(&mut self, x: f64) -> Result<(), StatsError> {
+        self.accumulator.push(x)?;
+        for q in &mut self.quantiles {
+            q.push(x)?;
+        }
+        if self.accumulator.count() as usize <= EXACT_LIMIT {
+            self.first.push(x);
+        } else if !self.first.is_empty() {
+            self.first = vec![];
+        }
+        Ok(())
+    }
+
+    pub fn accumulator(&self) -> &Accumulator {
+        &self.accumulator
+    }
+
+    // (percentile, estimate) in the order they were requested
+    pub fn quantiles(&self) -> Result<Vec<(f64, f64)>, StatsError> {
+        if self.first.is_empty() {
+            self.quantiles.iter().map(|q| q.estimate().map(|e| (q.percentile(), e))).collect()
+        } else {
+            self.quantiles.iter().map(|q| percentile(&self.first, q.percentile()).map(|e| (q.percentile(), e))).collect()
+        }
+    }
+}
+
+impl fmt::Display for StreamingSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let a = &self.accumulator;
+        write!(f, "count {}", a.count())?;
+        if let (Ok(mean), Ok(std_dev), Ok(min), Ok(max)) = (a.mean(), a.std_dev(), a.min(), a.max()) {
+            write!(f, ", mean {}, std dev {}, min {}, max {}", mean, std_dev, min, max)?;
+        }
+        if let Ok(quantiles) = self.quantiles() {
+            for (p, estimate) in quantiles {
+                write!(f, ", p{} ~{}", p, estimate)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stats;
+
+    // 0..n in a shuffled order, without 'rand' for the same numbers in every run
+    fn shuffled(n: u64) -> Vec<f64> {
+        (0..n).map(|i| (i * 7919) % n).map(|x| x as f64).collect()
+    }
+
+    #[test]
+    fn accumulator_is_exact() {
+        let data = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let mut a = Accumulator::new();
+        assert_eq!(a.mean(), Err(StatsError::Empty));
+        for &x in &data[..3] {
+            a.push(x).unwrap();
+        }
+        let mut b = Accumulator::new();
+        for &x in &data[3..] {
+            b.push(x).unwrap();
+        }
+        a.merge(&b);
+        assert_eq!(a.count(), 8);
+        assert_eq!(a.mean(), Ok(5.0));
+        assert_eq!(a.variance(), stats::variance(&data));
+        assert!((a.sample_variance().unwrap() - stats::sample_variance(&data).unwrap()).abs() < 1e-12);
+        assert_eq!((a.min(), a.max()), (Ok(2.0), Ok(9.0)));
+        assert_eq!(a.push(f64::NAN), Err(StatsError::NaN));
+    }
+
+    #[test]
+    fn p2_estimates_quantiles() {
+        let data = shuffled(10_000);
+        for &p in &[10.0, 50.0, 90.0, 99.0] {
+            let mut q = P2Quantile::new(p).unwrap();
+            for &x in &data {
+                q.push(x).unwrap();
+            }
+            let exact = stats::percentile(&data, p).unwrap();
+            assert!((q.estimate().unwrap() - exact).abs() < 50.0, "p{}: {} vs {}", p, q.estimate().unwrap(), exact);
+        }
+    }
+
+    #[test]
+    fn few_values_are_exact() {
+        let mut q = P2Quantile::new(50.0).unwrap();
+        assert_eq!(q.estimate(), Err(StatsError::Empty));
+        for &x in &[3.0, 1.0, 2.0, 10.0] {
+            q.push(x).unwrap();
+        }
+        assert_eq!(q.estimate(), Ok(2.5));
+        assert_eq!(P2Quantile::new(-1.0), Err(StatsError::InvalidPercentile(-1.0)));
+    }
+
+    #[test]
+    fn summary_is_exact_for_few_values() {
+        let data = shuffled(2000);
+        let mut summary = StreamingSummary::new(&[1.0, 99.0]).unwrap();
+        for &x in &data[..10] {
+            summary.push(x).unwrap();
+        }
+        let exact: Vec<(f64, f64)> = [1.0, 99.0].iter().map(|&p| (p, stats::percentile(&data[..10], p).unwrap())).collect();
+        assert_eq!(summary.quantiles(), Ok(exact));
+        for &x in &data[10..] {
+            summary.push(x).unwrap();
+        }
+        let quantiles = summary.quantiles().unwrap();
+        assert!((quantiles[1].1 - 1979.0).abs() < 20.0, "{:?}", quantiles);
+    }
+
+    #[test]
+    fn summary_is_printed() {
+        let mut summary = StreamingSummary::new(&[50.0]).unwrap();
+        assert_eq!(summary.to_string(), "count 0");
+        for &x in &[1.0, 2.0, 3.0] {
+            summary.push(x).unwrap();
+        }
+        assert_eq!(summary.to_string(), format!("count 3, mean 2, std dev {}, min 1, max 3, p50 ~2", (2.0f64 / 3.0).sqrt()));
+    }
+}
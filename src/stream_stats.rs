@@ -0,0 +1,65 @@
+// summary of numbers read line by line, nothing is kept in memory:
+//     seq 1 1000000 | cargo run --bin stream_stats
+//     cargo run --bin stream_stats -- --every 1000 numbers.txt
+// '--every N' prints the summary after every N lines too, lines which are not numbers are reported to stderr.
+
+extern crate myrust;
+
+use myrust::stats::streaming::StreamingSummary;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process::exit;
+
+const USAGE: &str = "usage: stream_stats [--every <lines>] [<file>]";
+const PERCENTILES: [f64; 5] = [1.0, 25.0, 50.0, 75.0, 99.0];
+
+fn main() {
+    let mut every: Option<usize> = None;
+    let mut path: Option<String> = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--every" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) if n > 0 => every = Some(n),
+                _ => {
+                    eprintln!("{}", USAGE);
+                    exit(2);
+                }
+            },
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => {
+                eprintln!("{}", USAGE);
+                exit(2);
+            }
+        }
+    }
+
+    let input: Box<dyn BufRead> = match path {
+        Some(ref path) => match File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(e) => {
+                eprintln!("Cannot open {}: {}", path, e);
+                exit(1);
+            }
+        },
+        None => Box::new(BufReader::new(io::stdin())),
+    };
+
+    let mut summary = StreamingSummary::new(&PERCENTILES).unwrap();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.expect("Cannot read input");
+        let line = line.trim();
+        if !line.is_empty() {
+            match line.parse::<f64>().map_err(|e| e.to_string()).and_then(|x| summary.push(x).map_err(|e| e.to_string())) {
+                Ok(()) => {}
+                Err(e) => eprintln!("line {}: '{}': {}", i + 1, line, e),
+            }
+        }
+        if every.is_some_and(|n| (i + 1) % n == 0) {
+            println!("after {} lines: {}", i + 1, summary);
+        }
+    }
+    println!("{}", summary);
+}
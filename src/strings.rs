@@ -30,7 +30,7 @@ fn main() {
         // Index<T> is not implemented for String, cannot access to chars by []-syntax
         let s = String::from("some string");
         compilation_error!(
-            let c = s[2]; // the type `String` cannot be indexed by `{integer}`
+            let c = s[2]; // the type `str` cannot be indexed by `{integer}`, required for `String` to implement `Index<{integer}>`
         );
         struct A { x: i32, y: i32 }
         impl Index<usize> for A {
@@ -33,7 +33,7 @@ fn main() {
 
         // user1.name was copied to user2.name, is it valid? Was it moved?
         compilation_error!(
-            println!("user: {:?}", user1); // error, use of moved value of field name
+            println!("user: {:?}", user1); // borrow of partially moved value: `user1`, partial move occurs because `user1.name` has type `String`
         );
         user1 = user2; // user1's object is destroyed, user2's object is moved to user1
         println!("user: {:?}", user1);
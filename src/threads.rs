@@ -6,6 +6,8 @@ use std::sync::mpsc;
 use std::time::Duration;
 use std::sync::mpsc::Sender;
 use std::collections::HashSet;
+use std::sync::Arc;
+use myrust::catcher::Catcher;
 
 fn main() {
     {
@@ -108,4 +110,50 @@ fn main() {
         }
         assert_eq!(storage, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10].iter().cloned().collect());
     }
+    {
+        // a cache for many threads, 'Catcher::value' takes '&mut self' and can not be shared
+        let slow_square = Arc::new(Catcher::new(|x: &u64| {
+            println!("calculating {} * {}", x, x); // printed once
+            thread::sleep(Duration::from_millis(100));
+            x * x
+        }).into_shared());
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let slow_square = slow_square.clone(); // 'Arc::clone', the cache is the same
+                thread::spawn(move || println!("thread {} got {:?}", i, slow_square.value(&9)))
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        println!("{:?}", slow_square.stats());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use myrust::journal;
+
+    #[test]
+    fn value_sent_through_channel_is_dropped_by_receiver() {
+        let recording = journal::start();
+        let (tx, rx) = mpsc::channel();
+        let t = thread::spawn(move || tx.send(Verbose::new(1)).unwrap());
+        let v = rx.recv().unwrap();
+        t.join().unwrap();
+        recording.assert_not_dropped(1); // it was moved to this thread
+        std::mem::drop(v);
+        recording.assert_dropped_on(1, thread::current().id());
+    }
+
+    #[test]
+    fn value_moved_into_thread_is_dropped_on_that_thread() {
+        let recording = journal::start();
+        let v = Verbose::new(2);
+        let t = thread::spawn(move || println!("spawned thread owns {:?}", v));
+        let spawned = t.thread().id();
+        t.join().unwrap();
+        recording.assert_dropped_on(2, spawned);
+    }
 }
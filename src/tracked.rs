@@ -0,0 +1,350 @@
+// 'Verbose' for any payload: Tracked<T> wraps a value and logs its creation, clones, moves into containers and drop.
+// Comparison and hashing are the ones of T, so a Tracked value can be a key of HashMap or an element of BTreeSet:
+//     let mut hm = HashMap::new();
+//     hm.insert(Tracked::labeled("key", String::from("Yellow")).moved_into("HashMap"), 10);
+// prints
+//     Creating key "Yellow"
+//     Moving key "Yellow" into HashMap
+//     Dropping key "Yellow"          <- when 'hm' goes out of scope
+
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display};
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering as AtomicOrdering};
+use std::thread;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Silent,
+    Text,      // Dropping key "Yellow"
+    JsonLines, // {"event":"dropped","label":"key","instance":3,"value":"\"Yellow\"","thread":"main"}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventKind {
+    Created,
+    Cloned { from: u64 },
+    Moved { into: String },
+    Dropped,
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub kind: EventKind,
+    pub label: String,
+    pub instance: u64,
+    pub value: String, // Debug of the payload
+    pub thread: Option<String>,
+}
+
+impl Event {
+    pub fn format(&self, output: Output) -> Option<String> {
+        match output {
+            Output::Silent => None,
+            Output::Text => Some(match self.kind {
+                EventKind::Created => format!("Creating {} {}", self.label, self.value),
+                EventKind::Cloned { .. } => format!("Cloning {} {}", self.label, self.value),
+                EventKind::Moved { ref into } => format!("Moving {} {} into {}", self.label, self.value, into),
+                EventKind::Dropped => format!("Dropping {} {}", self.label, self.value),
+            }),
+            Output::JsonLines => {
+                let (event, extra) = match self.kind {
+                    EventKind::Created => ("created", String::new()),
+                    EventKind::Cloned { from } => ("cloned", format!(",\"from\":{}", from)),
+                    EventKind::Moved { ref into } => ("moved", format!(",\"into\":{}", json_string(into))),
+                    EventKind::Dropped => ("dropped", String::new()),
+                };
+                Some(format!(
+                    "{{\"event\":\"{}\",\"label\":{},\"instance\":{}{},\"value\":{},\"thread\":{}}}",
+                    event,
+                    json_string(&self.label),
+                    self.instance,
+                    extra,
+                    json_string(&self.value),
+                    self.thread.as_ref().map(|t| json_string(t)).unwrap_or_else(|| String::from("null"))
+                ))
+            }
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+static OUTPUT: AtomicU8 = AtomicU8::new(1);
+static NEXT_INSTANCE: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    // events of this thread are collected here instead of printing, see 'capture'
+    static CAPTURED: RefCell<Option<(Output, Vec<String>)>> = const { RefCell::new(None) };
+}
+
+// the output of all Tracked values, 'Output::Text' by default
+pub fn set_output(output: Output) {
+    OUTPUT.store(output as u8, AtomicOrdering::Relaxed);
+}
+
+pub fn output() -> Output {
+    match OUTPUT.load(AtomicOrdering::Relaxed) {
+        0 => Output::Silent,
+        1 => Output::Text,
+        _ => Output::JsonLines,
+    }
+}
+
+// runs 'f' and returns lines it would print on this thread, for tests
+pub fn capture<F: FnOnce()>(output: Output, f: F) -> Vec<String> {
+    let restore = Restore(CAPTURED.with(|c| c.borrow_mut().replace((output, vec![]))));
+    f();
+    let lines = CAPTURED.with(|c| c.borrow_mut().take().map(|(_, lines)| lines).unwrap_or_default());
+    drop(restore);
+    lines
+}
+
+// puts back the capture which was there before, also when 'f' panics
+struct Restore(Option<(Output, Vec<String>)>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        let _ = CAPTURED.try_with(|c| *c.borrow_mut() = previous);
+    }
+}
+
+fn log(event: Event) {
+    let captured = CAPTURED.with(|c| match *c.borrow_mut() {
+        Some((output, ref mut lines)) => {
+            lines.extend(event.format(output));
+            true
+        }
+        None => false,
+    });
+    if !captured {
+        if let Some(line) = event.format(output()) {
+            println!("{}", line);
+        }
+    }
+}
+
+pub struct Tracked<T: Debug> {
+    value: Option<T>, // None only after 'into_inner' took it
+    label: String,
+    instance: u64,
+}
+
+impl<T: Debug> Tracked<T> {
+    pub fn new(value: T) -> Tracked<T> {
+        Tracked::labeled("Tracked", value)
+    }
+
+    pub fn labeled(label: &str, value: T) -> Tracked<T> {
+        let tracked = Tracked { value: Some(value), label: label.to_string(), instance: NEXT_INSTANCE.fetch_add(1, AtomicOrdering::Relaxed) };
+        tracked.log(EventKind::Created);
+        tracked
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn instance(&self) -> u64 {
+        self.instance
+    }
+
+    // a move can't be seen from inside, so it is logged by hand: 'v.push(x.moved_into("Vec"))'
+    pub fn moved_into(self, container: &str) -> Tracked<T> {
+        self.log(EventKind::Moved { into: container.to_string() });
+        self
+    }
+
+    // takes the payload out, nothing is dropped, so nothing is logged
+    pub fn into_inner(mut self) -> T {
+        self.value.take().expect("the payload is taken only once")
+    }
+
+    fn value(&self) -> &T {
+        self.value.as_ref().expect("the payload is taken only by 'into_inner'")
+    }
+
+    fn log(&self, kind: EventKind) {
+        log(Event {
+            kind,
+            label: self.label.clone(),
+            instance: self.instance,
+            value: format!("{:?}", self.value()),
+            thread: thread::current().name().map(String::from),
+        });
+    }
+}
+
+impl<T: Debug + Clone> Clone for Tracked<T> {
+    fn clone(&self) -> Tracked<T> {
+        let tracked = Tracked {
+            value: Some(self.value().clone()),
+            label: self.label.clone(),
+            instance: NEXT_INSTANCE.fetch_add(1, AtomicOrdering::Relaxed),
+        };
+        tracked.log(EventKind::Cloned { from: self.instance });
+        tracked
+    }
+}
+
+impl<T: Debug> Drop for Tracked<T> {
+    fn drop(&mut self) {
+        if self.value.is_some() {
+            self.log(EventKind::Dropped);
+        }
+    }
+}
+
+impl<T: Debug> Deref for Tracked<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.value()
+    }
+}
+
+impl<T: Debug> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().expect("the payload is taken only by 'into_inner'")
+    }
+}
+
+// 'hm.get("Yellow")' works for HashMap<Tracked<String>, _> because hashing is the same as of T
+impl<T: Debug> Borrow<T> for Tracked<T> {
+    fn borrow(&self) -> &T {
+        self.value()
+    }
+}
+
+impl Borrow<str> for Tracked<String> {
+    fn borrow(&self) -> &str {
+        self.value()
+    }
+}
+
+impl<T: Debug> Debug for Tracked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(self.value(), f)
+    }
+}
+
+impl<T: Debug + Display> Display for Tracked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(self.value(), f)
+    }
+}
+
+impl<T: Debug + PartialEq> PartialEq for Tracked<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value() == other.value()
+    }
+}
+
+impl<T: Debug + Eq> Eq for Tracked<T> {}
+
+impl<T: Debug + PartialOrd> PartialOrd for Tracked<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value().partial_cmp(other.value())
+    }
+}
+
+impl<T: Debug + Ord> Ord for Tracked<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value().cmp(other.value())
+    }
+}
+
+impl<T: Debug + Hash> Hash for Tracked<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value().hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeSet, HashMap};
+
+    #[test]
+    fn logs_lifecycle_of_a_map_key() {
+        let lines = capture(Output::Text, || {
+            let mut hm = HashMap::new();
+            hm.insert(Tracked::labeled("key", String::from("Yellow")).moved_into("HashMap"), 10);
+            assert_eq!(hm.get("Yellow"), Some(&10));
+        });
+        assert_eq!(lines, vec![
+            "Creating key \"Yellow\"",
+            "Moving key \"Yellow\" into HashMap",
+            "Dropping key \"Yellow\"",
+        ]);
+    }
+
+    #[test]
+    fn clone_and_json_lines() {
+        let lines = capture(Output::JsonLines, || {
+            let a = Tracked::labeled("n", 1);
+            let _b = a.clone();
+        });
+        assert_eq!(lines.len(), 4);
+        let a = lines[0].split("\"instance\":").nth(1).unwrap().split(',').next().unwrap().to_string();
+        assert!(lines[0].starts_with("{\"event\":\"created\",\"label\":\"n\","));
+        assert!(lines[1].contains("\"event\":\"cloned\"") && lines[1].contains(&format!("\"from\":{},", a)));
+        assert!(lines[2].starts_with("{\"event\":\"dropped\"") && lines[2].contains("\"value\":\"1\""));
+    }
+
+    #[test]
+    fn comparison_and_hashing_are_those_of_payload() {
+        let lines = capture(Output::Silent, || {
+            let set: BTreeSet<Tracked<i32>> = [3, 1, 2].iter().cloned().map(Tracked::new).collect();
+            assert_eq!(set.iter().map(|t| **t).collect::<Vec<_>>(), vec![1, 2, 3]);
+            assert_eq!(Tracked::labeled("a", 'x'), Tracked::labeled("b", 'x'));
+        });
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn into_inner_does_not_drop() {
+        let lines = capture(Output::Text, || {
+            let s = Tracked::labeled("s", String::from("text")).into_inner();
+            assert_eq!(s, "text");
+        });
+        assert_eq!(lines, vec!["Creating s \"text\""]);
+    }
+
+    #[test]
+    fn panic_in_capture_restores_the_previous_capture() {
+        let lines = capture(Output::Text, || {
+            let inner = std::panic::catch_unwind(|| capture(Output::Silent, || {
+                let _a = Tracked::labeled("a", 1);
+                panic!("in capture");
+            }));
+            assert!(inner.is_err());
+            let _ = Tracked::labeled("b", 2).into_inner();
+        });
+        assert_eq!(lines, vec!["Creating b 2"]);
+    }
+
+    #[test]
+    fn json_strings_are_escaped() {
+        assert_eq!(json_string("a\"b\\c\n\u{1}"), "\"a\\\"b\\\\c\\n\\u0001\"");
+    }
+}
@@ -208,12 +208,14 @@ fn main() {
         // error: internal compiler error: compiler/rustc_typeck/src/check/fn_ctxt/_impl.rs:526:17: no type for node HirId { owner: DefId(0:41 ~ traits[4135]::main), local_id: 364 }: type i32 (hir_id=HirId { owner: DefId(0:41 ~ traits[4135]::main), local_id: 364 }) in fcx 0x70000b479a30
         // let x: StructWithAccType1<B = i32> = StructWithAccType1{};
 
+        // rustc reports E0038 before E0191 "the value of the associated type `B` must be specified"
         compilation_error!(
-        // error[E0191]: the value of the associated type `B` (from trait `TraitWithAccType`) must be specified
+            // error[E0038]: the trait `TraitWithAccType` is not dyn compatible
             let x: Box<dyn TraitWithAccType> = Box::new(StructWithAccType {});
         );
+        // older rustc said "cannot be made into an object"
         compilation_error!(
-            // error[E0038]: the trait `TraitWithAccType` cannot be made into an object
+            // error[E0038]: the trait `TraitWithAccType` is not dyn compatible
             let x: Box<dyn TraitWithAccType<B = i32>> = Box::new(StructWithAccType {});
         );
     }
@@ -278,9 +280,9 @@ fn main() {
             }
         }
         println!("A baby dog is called a {}", Dog::baby_name()); // Spot
+        // compiler cannot chose between two implementations of 'baby_name'
         compilation_error!(
-            println!("A baby dog is called a {}", Animal::baby_name()); // type annotations needed
-            // compiler cannot chose between two implementations of 'baby_name'
+            println!("A baby dog is called a {}", Animal::baby_name()); // cannot call associated function on trait without specifying the corresponding `impl` type
         );
         println!("A baby dog is called a {}", <Dog as Animal>::baby_name()); // puppy
     }
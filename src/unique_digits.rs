@@ -0,0 +1,252 @@
+// numbers without repeated digits, in any base from 2 to 36:
+//     count_unique_digit_numbers(4000u32..5000, 10) == 504, 4xyz has 9 * 8 * 7 of them
+//     unique_digit_numbers(96u64..110, 10)          gives 96, 97, 98, 102, 103, ..., 109
+//     nth_unique_digit_number(10)                   == Some(10), 11 is the first one skipped
+// Nothing is tested number by number: counting picks digits one by one and multiplies the ways to fill
+// the rest, the iterator jumps over every block of numbers starting with a repeated prefix.
+// A base out of 2..=36 panics as in 'u32::from_str_radix'.
+
+use std::ops::Range;
+
+// an unsigned integer which fits in u128
+pub trait Unsigned: Copy + PartialOrd {
+    fn to_u128(self) -> u128;
+    fn from_u128(value: u128) -> Self; // the value is not bigger than some value of the type
+}
+
+macro_rules! unsigned {
+    ($($t:ty)*) => {
+        $(impl Unsigned for $t {
+            fn to_u128(self) -> u128 {
+                self as u128
+            }
+            fn from_u128(value: u128) -> $t {
+                value as $t
+            }
+        })*
+    };
+}
+
+unsigned!(u8 u16 u32 u64 u128 usize);
+
+pub fn has_unique_digits<T: Unsigned>(n: T, base: u32) -> bool {
+    first_repeat(&digits(n.to_u128(), base)).is_none()
+}
+
+// how many numbers of the range have unique digits
+pub fn count_unique_digit_numbers<T: Unsigned>(range: Range<T>, base: u32) -> u128 {
+    check_base(base);
+    if range.start >= range.end {
+        return 0;
+    }
+    count_below(range.end.to_u128(), base) - count_below(range.start.to_u128(), base)
+}
+
+pub fn unique_digit_numbers<T: Unsigned>(range: Range<T>, base: u32) -> UniqueDigitNumbers<T> {
+    check_base(base);
+    UniqueDigitNumbers { next: Some(range.start), end: range.end, base }
+}
+
+// in base 10, the first one (k = 0) is 0, None after 9876543210
+pub fn nth_unique_digit_number(k: u128) -> Option<u128> {
+    nth_unique_digit_number_in_base(k, 10)
+}
+
+// None when there are not so many of them or the number does not fit in u128
+pub fn nth_unique_digit_number_in_base(k: u128, base: u32) -> Option<u128> {
+    check_base(base);
+    if k == 0 {
+        return Some(0);
+    }
+    let (b, mut k) = (base as u128, k - 1); // k-th positive one
+    for length in 1..=base {
+        let of_length = (b - 1).saturating_mul(permutations(b - 1, length - 1));
+        if k >= of_length {
+            k -= of_length;
+            continue;
+        }
+        let (mut n, mut used) = (0u128, 0u64);
+        for i in 0..length {
+            // every digit leaves this many ways to fill the rest
+            let block = permutations(b - 1 - i as u128, length - 1 - i);
+            let first = if i == 0 { 1 } else { 0 };
+            let digit = (first..base).filter(|d| used & 1 << d == 0).nth((k / block) as usize)?;
+            k %= block;
+            used |= 1 << digit;
+            n = n.checked_mul(b)?.checked_add(digit as u128)?;
+        }
+        return Some(n);
+    }
+    None
+}
+
+// numbers of a range with unique digits in ascending order
+pub struct UniqueDigitNumbers<T: Unsigned> {
+    next: Option<T>, // where to look from, None when there is nothing more
+    end: T,
+    base: u32,
+}
+
+impl<T: Unsigned> Iterator for UniqueDigitNumbers<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let from = self.next?;
+        match next_unique(from.to_u128(), self.base) {
+            Some(n) if n < self.end.to_u128() => {
+                self.next = Some(T::from_u128(n + 1)); // n + 1 <= end, it fits
+                Some(T::from_u128(n))
+            }
+            _ => {
+                self.next = None;
+                None
+            }
+        }
+    }
+}
+
+fn check_base(base: u32) {
+    assert!((2..=36).contains(&base), "base {} is not in 2..=36", base);
+}
+
+// most significant first, 0 has one digit
+fn digits(mut n: u128, base: u32) -> Vec<u32> {
+    check_base(base);
+    let mut digits = vec![];
+    loop {
+        digits.push((n % base as u128) as u32);
+        n /= base as u128;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+    digits
+}
+
+fn first_repeat(digits: &[u32]) -> Option<usize> {
+    let mut used = 0u64;
+    for (i, &d) in digits.iter().enumerate() {
+        if used & 1 << d != 0 {
+            return Some(i);
+        }
+        used |= 1 << d;
+    }
+    None
+}
+
+// n! / (n - k)!, the ways to put k of n digits in a row, saturates as counts never get that big
+fn permutations(n: u128, k: u32) -> u128 {
+    (0..k as u128).fold(1u128, |p, i| p.saturating_mul(n.saturating_sub(i)))
+}
+
+// of 0..n
+fn count_below(n: u128, base: u32) -> u128 {
+    if n == 0 {
+        return 0;
+    }
+    let digits = digits(n, base);
+    let b = base as u128;
+    let length = digits.len() as u32;
+    // zero and all positive ones shorter than n
+    let mut count = 1;
+    for shorter in 1..length.min(base + 1) {
+        count += (b - 1) * permutations(b - 1, shorter - 1);
+    }
+    if length > base {
+        return count;
+    }
+    // as long as n, a digit smaller than n has there and anything unused after it
+    let mut used = 0u64;
+    for (i, &d) in digits.iter().enumerate() {
+        let first = if i == 0 { 1 } else { 0 };
+        let smaller = (first..d).filter(|v| used & 1 << v == 0).count() as u128;
+        count += smaller * permutations(b - 1 - i as u128, length - 1 - i as u32);
+        if used & 1 << d != 0 {
+            break;
+        }
+        used |= 1 << d;
+    }
+    count
+}
+
+// the smallest number with unique digits not less than n
+fn next_unique(mut n: u128, base: u32) -> Option<u128> {
+    let b = base as u128;
+    loop {
+        let digits = digits(n, base);
+        if digits.len() > base as usize {
+            return None;
+        }
+        let i = match first_repeat(&digits) {
+            Some(i) => i,
+            None => return Some(n),
+        };
+        // digits before i are unique, all numbers with the digit at i are skipped
+        let used = digits[..i].iter().fold(0u64, |used, d| used | 1 << d);
+        let prefix = digits[..i].iter().fold(0u128, |p, &d| p * b + d as u128);
+        if let Some(bigger) = (digits[i] + 1..base).find(|d| used & 1 << d == 0) {
+            // the smallest unused digits for the rest
+            let used = used | 1 << bigger;
+            let mut rest = (0..base).filter(|d| used & 1 << d == 0).take(digits.len() - i - 1);
+            return rest.try_fold(prefix * b + bigger as u128, |n, d| n.checked_mul(b)?.checked_add(d as u128));
+        }
+        // no digit fits at i, the prefix grows and the rest starts from zeros
+        n = (prefix + 1).checked_mul(b.checked_pow((digits.len() - i) as u32)?)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(range: Range<u128>, base: u32) -> Vec<u128> {
+        range.filter(|&n| has_unique_digits(n, base)).collect()
+    }
+
+    #[test]
+    fn counting_and_iterating_agree_with_brute_force() {
+        for base in 2..=10 {
+            let all = brute_force(0..2000, base);
+            for &(start, end) in &[(0, 0), (0, 1), (0, 2000), (7, 700), (98, 1234), (1000, 1001)] {
+                let expected: Vec<u128> = all.iter().cloned().filter(|n| (start..end).contains(n)).collect();
+                assert_eq!(count_unique_digit_numbers(start..end, base), expected.len() as u128, "base {} {}..{}", base, start, end);
+                assert_eq!(unique_digit_numbers(start..end, base).collect::<Vec<_>>(), expected, "base {}", base);
+            }
+        }
+        assert_eq!(count_unique_digit_numbers(4000u32..5000, 10), 504);
+        assert_eq!(unique_digit_numbers(96u64..110, 10).collect::<Vec<_>>(), vec![96, 97, 98, 102, 103, 104, 105, 106, 107, 108, 109]);
+    }
+
+    #[test]
+    fn big_ranges() {
+        assert_eq!(count_unique_digit_numbers(0..u64::MAX, 10), 8_877_691);
+        assert_eq!(unique_digit_numbers(0..u64::MAX, 10).last(), Some(9_876_543_210));
+        assert_eq!(count_unique_digit_numbers(10_000_000_000u64..u64::MAX, 10), 0);
+        assert_eq!(unique_digit_numbers(9_876_543_211u64..u64::MAX, 10).next(), None);
+        assert_eq!(unique_digit_numbers(u8::MAX - 1..u8::MAX, 2).next(), None);
+        // base 36 far out of u64
+        let start = 36u128.pow(20) + 12345;
+        assert_eq!(count_unique_digit_numbers(start..start + 50_000, 36), brute_force(start..start + 50_000, 36).len() as u128);
+        assert_eq!(unique_digit_numbers(start..start + 50_000, 36).collect::<Vec<_>>(), brute_force(start..start + 50_000, 36));
+    }
+
+    #[test]
+    fn nth() {
+        let first: Vec<u128> = unique_digit_numbers(0..5000, 10).collect();
+        for (k, &n) in first.iter().enumerate() {
+            assert_eq!(nth_unique_digit_number(k as u128), Some(n));
+        }
+        assert_eq!(nth_unique_digit_number(8_877_690), Some(9_876_543_210));
+        assert_eq!(nth_unique_digit_number(8_877_691), None);
+        assert_eq!(nth_unique_digit_number_in_base(3, 2), None); // 0, 1, 10 and nothing more
+        assert_eq!(nth_unique_digit_number_in_base(2, 2), Some(2));
+        assert_eq!(nth_unique_digit_number_in_base(u128::MAX, 36), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "base 1 is not in 2..=36")]
+    fn base_is_checked_for_an_empty_range_too() {
+        count_unique_digit_numbers(5u32..5, 1);
+    }
+}
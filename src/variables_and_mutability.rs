@@ -17,18 +17,20 @@ fn main() {
     x = ""; // ok, 'x' is mutable now, warning: value assigned to `x` is never read
 
     // however, types are checked
+    // expected type did not match the received type
     compilation_error!(
-        x = x.len(); // mismatched types. Expected type did not match the received type: expected `&str`, found `usize`
+        x = x.len(); // mismatched types, expected `&str`, found `usize`
     );
 
     const Y: i32 = 1; // explicitly typed, static, local scoped, substitutable
     // constant name cannot be shadowed
     compilation_error!(
-        const Y: i32 = 2; // name hase already being defined
+        const Y: i32 = 2; // the name `Y` is defined multiple times
     );
     let x = 1; // warning: unused variable: `x`, if this is intentional, prefix it with an underscore: `_x`
+    // x is non-constant value (but immutable!)
     compilation_error!(
-        const Z: i32 = 1 + x; // x is non-constant value (but immutable!)
+        const Z: i32 = 1 + x; // attempt to use a non-constant value in a constant
     );
     const Z: i32 = 1 + Y; // ok, Y is constant value
 
@@ -39,7 +41,7 @@ fn main() {
     let mut x_mut: i32 = 1;
     let x_ref: &mut i32 = &mut x_mut; // 'x_ref' is immutable
     compilation_error!(
-        x_ref = &2; // mismatched types, expected mutable reference `&mut i32`, found reference `&i32`
+        x_ref = &2; // mismatched types, expected mutable reference `&mut _`
         //      ^^ types differ in mutability
     );
     compilation_error!(
@@ -52,8 +54,9 @@ fn main() {
     let mut x_ref: &i32 = &mut x_mut;
     x_ref = &2;
     println!("Values of variable({}) and reference({})", x_mut, x_ref); // 1 2
+    // 'x_ref' is of type '&i32' - immutable
     compilation_error!(
-        *x_ref = 2; // cannot assign to `*x_ref` which is behind a `&` reference, because 'x_ref' is of type '&i32' - immutable
+        *x_ref = 2; // cannot assign to `*x_ref`, which is behind a `&` reference
     );
     {
         let mut x_ref: &mut i32 = &mut x_mut;
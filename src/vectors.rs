@@ -1,12 +1,5 @@
-#[derive(Debug)]
-struct Verbose {
-    number: i32
-}
-impl Drop for Verbose {
-    fn drop(&mut self) {
-        println!("Verbose object {} is dropped", self.number);
-    }
-}
+extern crate myrust;
+use myrust::Tracked;
 
 #[allow(unused)]
 fn main() {
@@ -49,10 +42,10 @@ println!("********* vectors");
         }
     }
     {
-        let v = vec![Verbose{ number: 1 }, Verbose{ number: 2 }];
+        let v = vec![Tracked::labeled("element", 1).moved_into("Vec"), Tracked::labeled("element", 2).moved_into("Vec")];
         // 'v' owns objects
-        println!("Vector of Verbose objects is {:?}", v);
-    } // 'v' was dropped => Verbose object 1 and Verbose object 2 was dropped
+        println!("Vector of tracked objects is {:?}", v);
+    } // 'v' was dropped => element 1 and element 2 was dropped
     {
         // iterating over a vector
         let mut v = vec![1, 2, 3];
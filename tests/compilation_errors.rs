@@ -0,0 +1,562 @@
+// compile-fail harness for 'compilation_error!'
+// the macro throws its input away, so the claims in the trailing comments are never checked by '$ cargo build'.
+// This test finds every 'compilation_error!' snippet in the package, puts its code back in place of the macro call
+// (all other snippets stay disabled), compiles the whole crate around it with rustc and checks that
+// compilation fails with the error from the comment.
+
+// The comment after a snippet is split by ',' '.' ';' into clauses, every one of them must be found in rustc's output,
+// so explanations go in comments outside of the snippet:
+//     // 'v' is invalid after it is moved
+//     compilation_error!(
+//         let vv = v; // use of moved value: `v`
+//     );
+// An error code like 'E0382' in the comment is checked against the codes rustc reports.
+// Wording which differs between versions of rustc is the same: case, quotes, punctuation, paths of types
+// ('std::cell::RefCell' is 'RefCell'), 'struct' and 'enum' before a type and the phrases in 'REWORDINGS'.
+
+// run only this harness: '$ cargo test --test compilation_errors -- --nocapture'
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Code,
+    Comment,
+    Literal,
+}
+
+// marks every byte of a source as code, comment or string/char literal
+fn classify(src: &str) -> Vec<Kind> {
+    let bytes = src.as_bytes();
+    let mut kinds = vec![Kind::Code; bytes.len()];
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let kind = if bytes[i..].starts_with(b"//") {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            Kind::Comment
+        } else if bytes[i..].starts_with(b"/*") {
+            let mut depth = 0;
+            while i < bytes.len() {
+                if bytes[i..].starts_with(b"/*") {
+                    depth += 1;
+                    i += 2;
+                } else if bytes[i..].starts_with(b"*/") {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            Kind::Comment
+        } else if let Some(hashes) = raw_string_start(bytes, i) {
+            // r#"..."#, the closing quote must be followed by the same number of '#'
+            while bytes[i] != b'"' {
+                i += 1;
+            }
+            i += 1;
+            let closing: Vec<u8> = std::iter::once(b'"').chain(std::iter::repeat_n(b'#', hashes)).collect();
+            while i < bytes.len() && !bytes[i..].starts_with(&closing) {
+                i += 1;
+            }
+            i = (i + closing.len()).min(bytes.len());
+            Kind::Literal
+        } else if bytes[i] == b'"' {
+            i += 1;
+            while i < bytes.len() && bytes[i] != b'"' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(bytes.len());
+            Kind::Literal
+        } else if bytes[i] == b'\'' && is_char_literal(src, i) {
+            i += 1;
+            while i < bytes.len() && bytes[i] != b'\'' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(bytes.len());
+            Kind::Literal
+        } else {
+            i += 1;
+            Kind::Code
+        };
+        for k in &mut kinds[start..i.min(bytes.len())] {
+            *k = kind;
+        }
+    }
+    kinds
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+// returns number of '#' if a raw string (r"", r#""#, br"") starts at 'i'
+fn raw_string_start(bytes: &[u8], i: usize) -> Option<usize> {
+    let mut j = i;
+    if bytes[j] == b'b' {
+        j += 1;
+    }
+    if j >= bytes.len() || bytes[j] != b'r' || (i > 0 && is_ident_byte(bytes[i - 1])) {
+        return None;
+    }
+    j += 1;
+    let hashes = bytes[j..].iter().take_while(|b| **b == b'#').count();
+    if bytes.get(j + hashes) == Some(&b'"') { Some(hashes) } else { None }
+}
+
+// 'a' and '\n' are char literals, 'a in '&'a str' is a lifetime
+fn is_char_literal(src: &str, i: usize) -> bool {
+    let mut rest = src[i + 1..].chars();
+    match rest.next() {
+        Some('\\') => true,
+        Some(_) => rest.next() == Some('\''),
+        None => false,
+    }
+}
+
+struct Snippet {
+    file: String,    // path relative to the package root
+    line: usize,
+    start: usize,    // the whole invocation, including a trailing ';'
+    end: usize,
+    body: String,
+    expected: Vec<String>,
+}
+
+const MACRO_NAME: &str = "compilation_error";
+
+fn find_snippets(file: &str, src: &str) -> Vec<Snippet> {
+    let bytes = src.as_bytes();
+    let kinds = classify(src);
+    let mut snippets = vec![];
+    let mut from = 0;
+    while let Some(pos) = src[from..].find(MACRO_NAME).map(|p| p + from) {
+        from = pos + MACRO_NAME.len();
+        if kinds[pos] != Kind::Code || (pos > 0 && is_ident_byte(bytes[pos - 1])) {
+            continue;
+        }
+        let mut i = from;
+        if bytes.get(i) != Some(&b'!') {
+            continue; // 'macro_rules! compilation_error' or a plain identifier
+        }
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if !matches!(bytes.get(i), Some(b'(' | b'[' | b'{')) {
+            continue; // not a call, the source may end right after the name
+        }
+        let body_start = i + 1;
+        let mut depth = 0;
+        loop {
+            if i == bytes.len() {
+                return snippets; // unclosed, rustc reports it
+            }
+            if kinds[i] == Kind::Code {
+                match bytes[i] {
+                    b'(' | b'[' | b'{' => depth += 1,
+                    b')' | b']' | b'}' => depth -= 1,
+                    _ => {}
+                }
+            }
+            if depth == 0 {
+                break;
+            }
+            i += 1;
+        }
+        let body_end = i;
+        let mut end = i + 1;
+        let after = src[end..].len() - src[end..].trim_start().len();
+        if bytes.get(end + after) == Some(&b';') {
+            end += after + 1;
+        }
+        // a call by path, 'myrust::compilation_error!(...)', is replaced as a whole
+        let mut start = pos;
+        while start >= 2 && &src[start - 2..start] == "::" {
+            start -= 2;
+            while start > 0 && is_ident_byte(bytes[start - 1]) {
+                start -= 1;
+            }
+        }
+        let expected = comments(src, &kinds, body_start, body_end)
+            .iter()
+            .flat_map(|c| clauses(c))
+            .collect();
+        snippets.push(Snippet {
+            file: file.to_string(),
+            line: src[..pos].matches('\n').count() + 1,
+            start,
+            end,
+            body: without_comments(src, &kinds, body_start, body_end),
+            expected,
+        });
+        from = end;
+    }
+    snippets
+}
+
+fn comments(src: &str, kinds: &[Kind], start: usize, end: usize) -> Vec<String> {
+    let mut result = vec![];
+    let mut i = start;
+    while i < end {
+        if kinds[i] == Kind::Comment && src[i..].starts_with("//") {
+            let mut j = i;
+            while j < end && kinds[j] == Kind::Comment {
+                j += 1;
+            }
+            result.push(src[i + 2..j].trim().to_string());
+            i = j;
+        } else {
+            i += 1;
+        }
+    }
+    result
+}
+
+// the code of a snippet with its comments blanked out, rustc quotes the lines it reports,
+// and a comment quoted in the output would match itself
+fn without_comments(src: &str, kinds: &[Kind], start: usize, end: usize) -> String {
+    src[start..end]
+        .char_indices()
+        .map(|(i, c)| if kinds[start + i] == Kind::Comment && c != '\n' { ' ' } else { c })
+        .collect()
+}
+
+// splits a comment by ',', '.' and ';' outside of `backticks`, drops the 'error:' prefix
+fn clauses(comment: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut current = String::new();
+    let mut in_backticks = false;
+    let mut chars = comment.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '`' {
+            in_backticks = !in_backticks;
+        }
+        let at_separator = !in_backticks
+            && (c == ';' || ((c == ',' || c == '.') && chars.peek().is_none_or(|n| n.is_whitespace())));
+        if at_separator {
+            result.push(current.clone());
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    result.push(current);
+    result
+        .into_iter()
+        .map(|c| {
+            let c = c.trim();
+            c.strip_prefix("error:").unwrap_or(c).trim().to_string()
+        })
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+fn is_error_code(clause: &str) -> bool {
+    clause.len() == 5 && clause.starts_with('E') && clause[1..].bytes().all(|b| b.is_ascii_digit())
+}
+
+// (older, newer) wording of rustc
+const REWORDINGS: &[(&str, &str)] = &[
+    ("cannot be made into an object", "is not dyn compatible"),
+    ("so the data it refers to cannot be written", "so it cannot be written to"),
+    ("in previous iteration of loop", "inside of this loop"),
+];
+
+// lowercase words, without quotes, punctuation, paths and the words rustc does not always put before a type
+fn words(text: &str) -> Vec<String> {
+    let mut text = text.to_lowercase();
+    for (older, newer) in REWORDINGS {
+        text = text.replace(older, newer);
+    }
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+        } else if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            word.clear(); // 'cell::' of 'cell::RefCell'
+        } else if !word.is_empty() {
+            if word != "struct" && word != "enum" {
+                words.push(word.clone());
+            }
+            word.clear();
+        }
+    }
+    if !word.is_empty() && word != "struct" && word != "enum" {
+        words.push(word);
+    }
+    words
+}
+
+fn matches(expected: &[String], stderr: &str) -> bool {
+    let normalized = stderr.split_whitespace().collect::<Vec<_>>().join(" ");
+    let output = words(stderr);
+    !expected.is_empty()
+        && expected.iter().all(|clause| {
+            if is_error_code(clause) {
+                normalized.contains(&format!("error[{}]", clause))
+            } else {
+                let clause = words(clause);
+                !clause.is_empty() && output.windows(clause.len()).any(|w| w == clause.as_slice())
+            }
+        })
+}
+
+struct Package {
+    root: PathBuf,
+    deps: PathBuf,
+    tmp: PathBuf,
+    crate_roots: Vec<String>,
+}
+
+impl Package {
+    fn new() -> Package {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        // this test binary lives in 'target/<profile>/deps' together with rlibs of the package's dependencies
+        let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+        let tmp = Path::new(env!("CARGO_TARGET_TMPDIR")).join("compilation_errors");
+        let manifest = fs::read_to_string(root.join("Cargo.toml")).unwrap();
+        let mut crate_roots = vec!["src/lib.rs".to_string()];
+        crate_roots.extend(
+            manifest
+                .lines()
+                .filter_map(|l| l.trim().strip_prefix("path = "))
+                .map(|p| p.trim_matches('"').to_string()),
+        );
+        crate_roots.extend(rust_files(&root, "tests"));
+        Package { root, deps, tmp, crate_roots }
+    }
+
+    // a file which is not a crate root is a module of the library
+    fn crate_root_of(&self, file: &str) -> String {
+        if self.crate_roots.iter().any(|r| r == file) {
+            file.to_string()
+        } else {
+            "src/lib.rs".to_string()
+        }
+    }
+
+    // an rlib, or a dynamic library of a procedural macro crate
+    fn newest_library(&self, name: &str) -> PathBuf {
+        let prefix = format!("lib{}-", name);
+        fs::read_dir(&self.deps)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| {
+                let file_name = p.file_name().unwrap().to_string_lossy();
+                file_name.starts_with(&prefix) && [".rlib", ".so", ".dylib", ".dll"].iter().any(|ext| file_name.ends_with(ext))
+            })
+            .max_by_key(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+            .unwrap_or_else(|| panic!("no library for '{}' in {:?}", name, self.deps))
+    }
+
+    // copies 'src' and 'tests' to a probe directory, replaces one file and compiles the crate root there
+    fn compile(&self, probe: &str, crate_root: &str, replaced: Option<(&str, &str)>) -> Result<(), String> {
+        let dir = self.tmp.join(probe);
+        let _ = fs::remove_dir_all(&dir);
+        for d in &["src", "tests"] {
+            copy_dir(&self.root.join(d), &dir.join(d));
+        }
+        if let Some((file, content)) = replaced {
+            fs::write(dir.join(file), content).unwrap();
+        }
+        let mut command = Command::new(env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string()));
+        command
+            .current_dir(&dir)
+            .arg(crate_root)
+            .args(["--edition", "2015", "--test", "--emit=mir", "-A", "warnings", "--color", "never"])
+            .arg("--crate-name")
+            .arg(if crate_root == "src/lib.rs" { "myrust".to_string() } else { crate_name(crate_root) })
+            .arg("-o")
+            .arg(dir.join("out.mir"))
+            .arg("-L")
+            .arg(format!("dependency={}", self.deps.display()));
+        let mut externs = vec!["rand", "unicode_segmentation", "memoize"];
+        if crate_root != "src/lib.rs" {
+            externs.push("myrust");
+        }
+        for name in externs {
+            command.arg("--extern").arg(format!("{}={}", name, self.newest_library(name).display()));
+        }
+        let output = command.output().expect("cannot run rustc");
+        let _ = fs::remove_dir_all(&dir);
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).into_owned())
+        }
+    }
+
+    fn check(&self, index: usize, snippet: &Snippet) -> Result<(), String> {
+        if snippet.expected.is_empty() {
+            return Err("no expected error in the comment".to_string());
+        }
+        let src = fs::read_to_string(self.root.join(&snippet.file)).unwrap();
+        let probe = format!("{}{}{}", &src[..snippet.start], snippet.body, &src[snippet.end..]);
+        let crate_root = self.crate_root_of(&snippet.file);
+        match self.compile(&format!("probe{}", index), &crate_root, Some((&snippet.file, &probe))) {
+            Ok(()) => Err(format!("compiles, but expected error: {}", snippet.expected.join(", "))),
+            Err(ref stderr) if matches(&snippet.expected, stderr) => Ok(()),
+            Err(stderr) => Err(format!(
+                "fails with other error, expected: {}\n{}",
+                snippet.expected.join(", "),
+                stderr.lines().filter(|l| l.starts_with("error")).collect::<Vec<_>>().join("\n")
+            )),
+        }
+    }
+}
+
+fn crate_name(path: &str) -> String {
+    Path::new(path).file_stem().unwrap().to_string_lossy().into_owned()
+}
+
+fn rust_files(root: &Path, dir: &str) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir(root.join(dir))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "rs"))
+        .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    files
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let path = entry.unwrap().path();
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target);
+        } else {
+            fs::copy(&path, &target).unwrap();
+        }
+    }
+}
+
+fn all_source_files(root: &Path, dir: &str) -> Vec<String> {
+    let mut files = vec![];
+    for entry in fs::read_dir(root.join(dir)).unwrap() {
+        let path = entry.unwrap().path();
+        let relative = path.strip_prefix(root).unwrap().to_string_lossy().into_owned();
+        if path.is_dir() {
+            files.extend(all_source_files(root, &relative));
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            files.push(relative);
+        }
+    }
+    files.sort();
+    files
+}
+
+#[test]
+fn every_compilation_error_snippet_fails_to_compile_with_the_commented_error() {
+    let package = Package::new();
+    let this_file = file!().replace('\\', "/");
+    let mut snippets = vec![];
+    for file in all_source_files(&package.root, "src").into_iter().chain(rust_files(&package.root, "tests")) {
+        if file != this_file {
+            let src = fs::read_to_string(package.root.join(&file)).unwrap();
+            snippets.extend(find_snippets(&file, &src));
+        }
+    }
+    assert!(!snippets.is_empty(), "no 'compilation_error!' snippets found");
+
+    // without snippets every crate must compile, otherwise the harness itself is broken
+    let mut roots: Vec<String> = snippets.iter().map(|s| package.crate_root_of(&s.file)).collect();
+    roots.sort();
+    roots.dedup();
+    for (i, root) in roots.iter().enumerate() {
+        if let Err(stderr) = package.compile(&format!("control{}", i), root, None) {
+            panic!("{} does not compile even without snippets:\n{}", root, stderr);
+        }
+    }
+
+    let workers = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut results: Vec<(usize, Result<(), String>)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|w| {
+                let package = &package;
+                let snippets = &snippets;
+                scope.spawn(move || {
+                    (w..snippets.len())
+                        .step_by(workers)
+                        .map(|i| (i, package.check(i, &snippets[i])))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+    });
+    results.sort_by_key(|(i, _)| *i);
+
+    let mut failures = vec![];
+    for (i, result) in results {
+        let snippet = &snippets[i];
+        match result {
+            Ok(()) => println!("ok   {}:{}", snippet.file, snippet.line),
+            Err(e) => {
+                println!("FAIL {}:{}: {}", snippet.file, snippet.line, e);
+                failures.push(format!("{}:{}: {}", snippet.file, snippet.line, e));
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} of {} 'compilation_error!' snippets do not fail as commented:\n{}",
+        failures.len(),
+        snippets.len(),
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn snippet_is_found_with_its_comment_and_line() {
+    let src = "fn main() {\n    let s = \"compilation_error!(\";\n    compilation_error!(\n        let vv = v; // use of moved value: `v`, E0382\n    );\n}\n";
+    let snippets = find_snippets("src/x.rs", src);
+    assert_eq!(snippets.len(), 1);
+    assert_eq!(snippets[0].line, 3);
+    assert_eq!(snippets[0].body.trim(), "let vv = v;"); // the comment is blanked out
+    assert_eq!(snippets[0].expected, vec!["use of moved value: `v`", "E0382"]);
+    assert_eq!(&src[snippets[0].end..], "\n}\n");
+}
+
+#[test]
+fn clauses_keep_backticked_commas_and_error_codes_are_checked() {
+    assert_eq!(clauses("error: expected `Result<i32, String>`, found `i32`"), vec!["expected `Result<i32, String>`", "found `i32`"]);
+    assert!(matches(&["E0382".to_string()], "error[E0382]: borrow of moved value: `v`"));
+    assert!(!matches(&["E0499".to_string()], "error[E0382]: borrow of moved value: `v`"));
+    // every clause, and something
+    let stderr = "error[E0382]: borrow of moved value: `v`";
+    assert!(matches(&["E0382".to_string(), "borrow of moved value".to_string()], stderr));
+    assert!(!matches(&["borrow of moved value".to_string(), "'v' is invalid".to_string()], stderr));
+    assert!(!matches(&[], stderr));
+}
+
+#[test]
+fn wording_of_other_rustc_versions_matches() {
+    let stderr = "error[E0038]: the trait `Clone` is not dyn compatible\n  |     ^ expected `std::string::String`, found integer";
+    assert!(matches(&["the trait `Clone` cannot be made into an object".to_string()], stderr));
+    assert!(matches(&["expected struct `String`".to_string(), "found integer".to_string()], stderr));
+    assert!(matches(&["EXPECTED 'String'".to_string()], stderr));
+    assert!(!matches(&["expected `str`".to_string()], stderr)); // whole words
+    assert!(!matches(&["found `String`".to_string()], stderr));
+}
+
+#[test]
+fn whitespace_at_the_end_of_a_source_is_not_a_snippet() {
+    assert!(find_snippets("src/x.rs", "// compilation_error! \n").is_empty());
+    assert!(find_snippets("src/x.rs", "fn main() {}\ncompilation_error!   ").is_empty());
+}
@@ -0,0 +1,148 @@
+// 'runtime_error!' and 'debug_runtime_error!' called from a test always run their statement, see src/checks.rs
+// with '$ cargo test --features check-runtime-errors' lesson binaries are run as well,
+// each of their runtime error examples must panic as annotated, run it with '--release' too
+
+#[macro_use]
+extern crate myrust;
+
+#[test]
+fn statement_panics_with_expected_message() {
+    let v: Vec<i32> = vec![];
+    runtime_error!(
+        "index out of bounds: the len is 0 but the index is 10",
+        let x = v[10];
+    );
+}
+
+#[test]
+fn any_panic_is_accepted_without_message() {
+    runtime_error!(
+        panic!("whatever")
+    );
+}
+
+#[test]
+#[should_panic(expected = "statement did not panic")]
+fn statement_that_does_not_panic_is_an_error() {
+    let v = [1];
+    runtime_error!(
+        "index out of bounds",
+        let x = v[0];
+    );
+}
+
+#[test]
+#[should_panic(expected = "expected 'attempt to divide by zero'")]
+fn statement_that_panics_with_other_message_is_an_error() {
+    runtime_error!(
+        "attempt to divide by zero",
+        let x: i32 = "x".parse().unwrap();
+    );
+}
+
+#[test]
+fn overflow_panics_in_debug_build_and_wraps_in_release_build() {
+    let x: u8 = 255;
+    debug_runtime_error!(
+        "attempt to add with overflow", release == 0,
+        let y = x + 1;
+    );
+    debug_runtime_error!(
+        "attempt to multiply with overflow", release == 254,
+        x * 2
+    );
+}
+
+#[test]
+fn statement_must_not_panic_in_release_build() {
+    let x: i32 = i32::MIN;
+    debug_runtime_error!(
+        "attempt to negate with overflow",
+        let y = -x;
+    );
+}
+
+#[test]
+#[cfg(not(debug_assertions))]
+#[should_panic(expected = "gives 0 in release build, expected 1")]
+fn wrong_release_value_is_an_error() {
+    let x: u8 = 255;
+    debug_runtime_error!(
+        "attempt to add with overflow", release == 1,
+        x + 1
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "statement did not panic")]
+fn statement_that_does_not_panic_in_debug_build_is_an_error() {
+    let x: u8 = 254;
+    debug_runtime_error!(
+        "attempt to add with overflow", release == 255,
+        x + 1
+    );
+}
+
+#[cfg(feature = "check-runtime-errors")]
+mod lessons {
+    use std::fs;
+    use std::io::Write;
+    use std::path::Path;
+    use std::process::{Command, Stdio};
+
+    // lesson binaries which have 'runtime_error!' examples and their stdin
+    const BINARIES: &[(&str, &str, &str)] = &[
+        ("src/data_types.rs", env!("CARGO_BIN_EXE_data_types"), ""),
+        ("src/data_types_prg.rs", env!("CARGO_BIN_EXE_data_types_prg"), "2\n"),
+        ("src/enums.rs", env!("CARGO_BIN_EXE_enums"), ""),
+        ("src/smart_pointers_rc.rs", env!("CARGO_BIN_EXE_smart_pointers_rc"), ""),
+    ];
+
+    // paths like "src/front_of_house/hosting.rs", modules of the library are in directories
+    fn all_source_files(root: &Path, dir: &str) -> Vec<String> {
+        let mut files = vec![];
+        for entry in fs::read_dir(root.join(dir)).unwrap() {
+            let path = entry.unwrap().path();
+            let relative = path.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/");
+            if path.is_dir() {
+                files.extend(all_source_files(root, &relative));
+            } else if path.extension().is_some_and(|e| e == "rs") {
+                files.push(relative);
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn every_lesson_with_runtime_errors_is_run() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for file in all_source_files(root, "src") {
+            let text = fs::read_to_string(root.join(&file)).unwrap();
+            // 'debug_runtime_error!' too
+            if text.contains(concat!("runtime_error", "!(")) && file != "src/lib.rs" {
+                assert!(BINARIES.iter().any(|(f, _, _)| *f == file), "{} has runtime_error! examples, add it to BINARIES", file);
+            }
+        }
+    }
+
+    #[test]
+    fn runtime_errors_of_lessons_panic_as_annotated() {
+        for (file, binary, input) in BINARIES {
+            let mut child = Command::new(binary)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .unwrap();
+            child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+            let output = child.wait_with_output().unwrap();
+            assert!(
+                output.status.success(),
+                "{} failed:\n{}",
+                file,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    }
+}